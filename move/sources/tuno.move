@@ -15,6 +15,7 @@ module tuno::tuno {
     const ENotAvailable: u64 = 4;
     const EAlreadyAvailable: u64 = 5;
     const ESongNotInKiosk: u64 = 6;
+    const EDisplayInUse: u64 = 7;
     
     // ======== Events ========
 
@@ -36,6 +37,13 @@ module tuno::tuno {
         kiosk_id: ID
     }
     
+    public struct SongUpdated<phantom T: drop> has copy, drop {
+        id: ID,
+        title: String,
+        artist: String,
+        streaming_price: u64
+    }
+    
     public struct DistributorAdded<phantom T: drop> has copy, drop {
         song_id: ID,
         distributor: address,
@@ -195,16 +203,7 @@ module tuno::tuno {
         
         assert!(song_display.song_id == object::id(song), ESongNotInKiosk);
         
-        let SongDisplay {
-            id,
-            song_id: _,
-            title: _,
-            artist: _,
-            genre: _,
-            streaming_price: _,
-            cover_art_url: _,
-        } = song_display;
-        object::delete(id);
+        delete_song_display(song_display);
         
         song.display_id = option::none();
         
@@ -214,6 +213,76 @@ module tuno::tuno {
         });
     }
     
+    // Removes a display left in the kiosk by its song, which no longer refers to it.
+    // The display the song is listed with is removed with `make_song_unavailable`
+    public entry fun remove_song_display<T: drop>(
+        song: &Song<T>,
+        kiosk: &mut Kiosk,
+        cap: &KioskOwnerCap,
+        display_id: ID
+    ) {
+        assert!(song.display_id != option::some(display_id), EDisplayInUse);
+
+        let song_display = kiosk::take<SongDisplay<T>>(kiosk, cap, display_id);
+
+        assert!(song_display.song_id == object::id(song), ESongNotInKiosk);
+
+        delete_song_display(song_display);
+    }
+    
+    public entry fun update_song_metadata<T: drop>(
+        song: &mut Song<T>,
+        title: vector<u8>,
+        artist: vector<u8>,
+        album: vector<u8>,
+        release_year: u64,
+        genre: vector<u8>,
+        cover_art_url: vector<u8>,
+        streaming_price: u64,
+        ctx: &mut TxContext
+    ) {
+        let sender = tx_context::sender(ctx);
+        assert!(sender == song.owner, ENotOwner);
+
+        song.title = string::utf8(title);
+        song.artist = string::utf8(artist);
+        song.album = string::utf8(album);
+        song.release_year = release_year;
+        song.genre = string::utf8(genre);
+        song.cover_art_url = string::utf8(cover_art_url);
+        song.streaming_price = streaming_price;
+
+        event::emit(SongUpdated<T> {
+            id: object::id(song),
+            title: song.title,
+            artist: song.artist,
+            streaming_price
+        });
+    }
+    
+    // Copies the song's metadata to its display, so that kiosk listings don't go stale
+    public entry fun refresh_song_display<T: drop>(
+        song: &Song<T>,
+        kiosk: &mut Kiosk,
+        cap: &KioskOwnerCap,
+        ctx: &mut TxContext
+    ) {
+        let sender = tx_context::sender(ctx);
+        assert!(sender == song.owner, ENotOwner);
+
+        assert!(is_available(song), ENotAvailable);
+
+        let song_display = kiosk::borrow_mut<SongDisplay<T>>(kiosk, cap, *song.display_id.borrow());
+
+        assert!(song_display.song_id == object::id(song), ESongNotInKiosk);
+
+        song_display.title = song.title;
+        song_display.artist = song.artist;
+        song_display.genre = song.genre;
+        song_display.streaming_price = song.streaming_price;
+        song_display.cover_art_url = song.cover_art_url;
+    }
+    
     public entry fun register_as_distributor<T: drop>(
         song: &mut Song<T>,
         url: vector<u8>,
@@ -377,6 +446,17 @@ module tuno::tuno {
         )
     }
     
+    // Get the metadata shown in kiosk listings
+    public fun get_song_display_info<T: drop>(song_display: &SongDisplay<T>): (String, String, String, u64, String) {
+        (
+            song_display.title,
+            song_display.artist,
+            song_display.genre,
+            song_display.streaming_price,
+            song_display.cover_art_url
+        )
+    }
+    
     // Get total streaming price (creator + distributor)
     public fun get_total_price<T: drop>(song: &Song<T>, distributor: address): u64 {
         assert!(vec_map::contains(&song.distributors, &distributor), ENotDistributor);
@@ -393,4 +473,44 @@ module tuno::tuno {
     public fun is_available<T: drop>(song: &Song<T>): bool {
         song.display_id.is_some()
     }
+    
+    // ======== Test Functions ========
+
+    // Places a display the song does not refer to, as left by an earlier listing
+    #[test_only]
+    public fun place_orphan_display_for_testing<T: drop>(
+        song: &Song<T>,
+        kiosk: &mut Kiosk,
+        cap: &KioskOwnerCap,
+        ctx: &mut TxContext
+    ): ID {
+        let song_display = SongDisplay<T> {
+            id: object::new(ctx),
+            song_id: object::id(song),
+            title: song.title,
+            artist: song.artist,
+            genre: song.genre,
+            streaming_price: song.streaming_price,
+            cover_art_url: song.cover_art_url,
+        };
+        let display_id = object::id(&song_display);
+
+        kiosk::place(kiosk, cap, song_display);
+        display_id
+    }
+
+    // ======== Private Functions ========
+    
+    fun delete_song_display<T: drop>(song_display: SongDisplay<T>) {
+        let SongDisplay {
+            id,
+            song_id: _,
+            title: _,
+            artist: _,
+            genre: _,
+            streaming_price: _,
+            cover_art_url: _,
+        } = song_display;
+        object::delete(id);
+    }
 }
@@ -0,0 +1,98 @@
+#[test_only]
+module tuno::bcs_tests {
+    use std::bcs;
+    use iota::iota::IOTA;
+    use iota::test_scenario;
+    use iota::test_utils::assert_eq;
+    use iota::kiosk::{Self, Kiosk, KioskOwnerCap};
+    use tuno::tuno::{Self, CreatorCap, Song, SongDisplay};
+
+    use tuno::constants::get_creator;
+
+    use tuno::utils::{
+        setup_creator,
+        create_test_song,
+        place_song_on_kiosk,
+    };
+
+    // Golden bytes of the test fixtures, decoded by the mirrors of `tuno-cli/src/objects.rs`.
+    // Object ids are left out as they depend on the scenario.
+
+    const SONG_BYTES: vector<u8> = x"095465737420536f6e670b54657374204172746973740a5465737420416c62756de9070000000000000a456c656374726f6e69631c687474703a2f2f6578616d706c652e636f6d2f636f7665722e6a7067809698000000000000000000000000000000000000000000000000000000000000000000000000c100000001000000005a0000000000000002030102030304050600000000000000000000";
+    const CREATOR_CAP_BYTES: vector<u8> = x"00000000000000000000000000000000000000000000000000000000000000c1";
+    const SONG_DISPLAY_BYTES: vector<u8> = x"095465737420536f6e670b54657374204172746973740a456c656374726f6e696380969800000000001c687474703a2f2f6578616d706c652e636f6d2f636f7665722e6a7067";
+
+    // Bytes following the `skip` first bytes of the serialized value
+    fun bytes_after<T>(value: &T, skip: u64): vector<u8> {
+        let bytes = bcs::to_bytes(value);
+        let mut tail = vector[];
+        let mut i = skip;
+        while (i < bytes.length()) {
+            tail.push_back(bytes[i]);
+            i = i + 1;
+        };
+
+        tail
+    }
+
+    #[test]
+    fun test_song_layout() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+
+            // Skip the UID
+            assert_eq(bytes_after(&song, 32), SONG_BYTES);
+
+            test_scenario::return_shared(song);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test]
+    fun test_creator_cap_layout() {
+        let mut scenario = setup_creator();
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let cap = test_scenario::take_from_sender<CreatorCap>(&scenario);
+
+            // Skip the UID
+            assert_eq(bytes_after(&cap, 32), CREATOR_CAP_BYTES);
+
+            test_scenario::return_to_sender(&scenario, cap);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test]
+    fun test_song_display_layout() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        place_song_on_kiosk(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            let (_, _, _, _, _, _, _, display_id) = tuno::get_song_info(&song);
+            let display = kiosk::borrow<SongDisplay<IOTA>>(&kiosk, &cap, display_id.destroy_some());
+
+            // Skip the UID and the song's ID
+            assert_eq(bytes_after(display, 64), SONG_DISPLAY_BYTES);
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+}
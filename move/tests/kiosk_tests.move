@@ -0,0 +1,88 @@
+#[test_only]
+module tuno::kiosk_tests {
+    use iota::iota::IOTA;
+    use iota::test_scenario;
+    use iota::test_utils::assert_eq;
+    use iota::kiosk::{Self, Kiosk, KioskOwnerCap};
+    use tuno::tuno::{Self, Song};
+
+    use tuno::constants::get_creator;
+
+    use tuno::utils::{
+        setup_creator,
+        create_test_song,
+        place_song_on_kiosk,
+    };
+
+    #[test]
+    fun test_remove_song_display() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        place_song_on_kiosk(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let mut kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            let orphan_id = tuno::place_orphan_display_for_testing(&song, &mut kiosk, &cap, test_scenario::ctx(&mut scenario));
+            assert_eq(kiosk::item_count(&kiosk), 2);
+
+            tuno::remove_song_display<IOTA>(&song, &mut kiosk, &cap, orphan_id);
+
+            assert_eq(kiosk::item_count(&kiosk), 1);
+            assert!(tuno::is_available(&song));
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test, expected_failure(abort_code = tuno::tuno::EDisplayInUse)]
+    fun test_remove_song_display_in_use() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        place_song_on_kiosk(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let mut kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            let (_, _, _, _, _, _, _, display_id) = tuno::get_song_info(&song);
+            tuno::remove_song_display<IOTA>(&song, &mut kiosk, &cap, display_id.destroy_some());
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test, expected_failure]
+    fun test_remove_song_display_not_in_kiosk() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let mut kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            tuno::remove_song_display<IOTA>(&song, &mut kiosk, &cap, object::id(&song));
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+}
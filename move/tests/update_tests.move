@@ -0,0 +1,130 @@
+#[test_only]
+module tuno::update_tests {
+    use std::string;
+    use iota::iota::IOTA;
+    use iota::test_scenario;
+    use iota::test_utils::assert_eq;
+    use iota::kiosk::{Self, Kiosk, KioskOwnerCap};
+    use tuno::tuno::{Self, Song, SongDisplay};
+
+    use tuno::constants::{
+        get_creator,
+        get_user,
+    };
+
+    use tuno::utils::{
+        setup_creator,
+        create_test_song,
+        place_song_on_kiosk,
+    };
+
+    const NEW_STREAMING_PRICE: u64 = 20_000_000;
+
+    fun update_test_song(scenario: &mut test_scenario::Scenario, sender: address) {
+        test_scenario::next_tx(scenario, sender);
+        {
+            let mut song = test_scenario::take_shared<Song<IOTA>>(scenario);
+
+            tuno::update_song_metadata(
+                &mut song,
+                b"New Title",
+                b"New Artist",
+                b"New Album",
+                2026,
+                b"Ambient",
+                b"http://example.com/new-cover.jpg",
+                NEW_STREAMING_PRICE,
+                test_scenario::ctx(scenario)
+            );
+
+            test_scenario::return_shared(song);
+        };
+    }
+
+    #[test]
+    fun test_update_song_metadata() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        update_test_song(&mut scenario, get_creator());
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let (title, artist, album, year, genre, price, _, _) = tuno::get_song_info(&song);
+
+            assert_eq(title, string::utf8(b"New Title"));
+            assert_eq(artist, string::utf8(b"New Artist"));
+            assert_eq(album, string::utf8(b"New Album"));
+            assert_eq(year, 2026);
+            assert_eq(genre, string::utf8(b"Ambient"));
+            assert_eq(price, NEW_STREAMING_PRICE);
+
+            test_scenario::return_shared(song);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test, expected_failure(abort_code = tuno::tuno::ENotOwner)]
+    fun test_update_song_metadata_not_owner() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        update_test_song(&mut scenario, get_user());
+
+        test_scenario::end(scenario);
+    }
+
+    #[test]
+    fun test_refresh_song_display() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+        place_song_on_kiosk(&mut scenario);
+        update_test_song(&mut scenario, get_creator());
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let mut kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            tuno::refresh_song_display(&song, &mut kiosk, &cap, test_scenario::ctx(&mut scenario));
+
+            let (_, _, _, _, _, _, _, display_id) = tuno::get_song_info(&song);
+            let song_display = kiosk::borrow<SongDisplay<IOTA>>(&kiosk, &cap, display_id.destroy_some());
+            let (title, artist, genre, price, cover_art_url) = tuno::get_song_display_info(song_display);
+
+            assert_eq(title, string::utf8(b"New Title"));
+            assert_eq(artist, string::utf8(b"New Artist"));
+            assert_eq(genre, string::utf8(b"Ambient"));
+            assert_eq(price, NEW_STREAMING_PRICE);
+            assert_eq(cover_art_url, string::utf8(b"http://example.com/new-cover.jpg"));
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+
+    #[test, expected_failure(abort_code = tuno::tuno::ENotAvailable)]
+    fun test_refresh_song_display_not_available() {
+        let mut scenario = setup_creator();
+        create_test_song(&mut scenario);
+
+        test_scenario::next_tx(&mut scenario, get_creator());
+        {
+            let song = test_scenario::take_shared<Song<IOTA>>(&scenario);
+            let mut kiosk = test_scenario::take_shared<Kiosk>(&scenario);
+            let cap = test_scenario::take_from_sender<KioskOwnerCap>(&scenario);
+
+            tuno::refresh_song_display(&song, &mut kiosk, &cap, test_scenario::ctx(&mut scenario));
+
+            test_scenario::return_shared(song);
+            test_scenario::return_to_sender(&scenario, cap);
+            test_scenario::return_shared(kiosk);
+        };
+
+        test_scenario::end(scenario);
+    }
+}
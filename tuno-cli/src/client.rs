@@ -1,19 +1,43 @@
+use std::collections::HashSet;
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
+use std::time::Duration;
 
-use iota_sdk::rpc_types::{IotaExecutionResult, IotaExecutionStatus, IotaMoveValue, IotaParsedData, IotaTransactionBlockEffectsAPI as _, IotaTransactionBlockResponse};
-use iota_sdk::types::Identifier;
+use iota_sdk::rpc_types::{Coin, IotaExecutionResult, IotaExecutionStatus, IotaRawData, IotaTransactionBlockEffectsAPI as _, IotaTransactionBlockResponse};
+use iota_sdk::types::{Identifier, TypeTag, IOTA_FRAMEWORK_PACKAGE_ID};
 use iota_sdk::types::digests::TransactionDigest;
 use iota_sdk::types::base_types::{IotaAddress, ObjectID, ObjectRef};
 use iota_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
-use iota_sdk::types::transaction::{Argument, Command, ObjectArg, ProgrammableTransaction, Transaction, TransactionData, TransactionKind};
+use iota_sdk::types::transaction::{
+    Argument, Command, ObjectArg, ProgrammableTransaction, Transaction, TransactionData, TransactionDataAPI as _, TransactionKind
+};
 use iota_sdk::wallet_context::WalletContext;
 
-use anyhow::{bail, Result};
+use anyhow::{anyhow, bail, Context as _, Result};
 use clap::Parser;
-use log::{error, info, trace};
-
-use crate::local_storage::{get_all_song_ids, FileMetadata};
-use crate::types::{Song, SongDisplay, SongDisplayList, SongList};
+use serde::{Deserialize, Serialize};
+use futures::{stream, StreamExt as _};
+use log::{error, info, trace, warn};
+
+use crate::constants::{
+    DEFAULT_GAS_MULTIPLIER, DEFAULT_NETWORK, MAX_BATCH_ARGS_SIZE, TUNO_BASE_CHUNK_SIZE, MAX_GAS_BUDGET, MAX_MERGE_COINS_PER_TX, MAX_SONGS_PER_BATCH,
+    TUNO_MODULE, CREATE_SONG_FN, GET_TOTAL_PRICE_FN, MAKE_SONG_AVAILABLE_FN, MAKE_SONG_UNAVAILABLE_FN,
+    PAY_ROYALTIES_FN, REFRESH_SONG_DISPLAY_FN, REGISTER_AS_DISTRIBUTOR_FN, REGISTER_CREATOR_FN,
+    REMOVE_AS_DISTRIBUTOR_FN, REMOVE_SONG_DISPLAY_FN, UPDATE_SONG_METADATA_FN,
+    KIOSK_MODULE, KIOSK_SET_OWNER_CUSTOM_FN, KIOSK_TAKE_FN, KIOSK_WITHDRAW_FN
+};
+use crate::compat::{check_package, Compatibility};
+use crate::distribution_commands::pb;
+use crate::errors::{Error, TunoError};
+use crate::gas_pool::GasPool;
+use crate::indexer::Indexer;
+use crate::local_storage::{get_all_song_ids, FileMetadata, TrackTags};
+use crate::objects::{
+    parse_object, parse_objects, struct_tag, MoveCreatorCap, MoveKiosk, MoveKioskOwnerCap, MoveSong,
+    MoveSongDisplay, ParseError
+};
+use crate::profiles::Profiles;
+use crate::types::{CreatorCap, KioskCap, KioskInfo, Song, SongDisplay, SongDisplayList, SongList};
 use crate::utils::*;
 
 #[derive(Parser, Clone)]
@@ -25,17 +49,87 @@ pub struct Connection {
     /// Object ID of the game's package.
     #[arg(long, short, env = "PKG")]
     pub package_id: ObjectID,
+
+    /// Saved profile providing the creator and kiosk capabilities (default: the active profile, see `profile list`)
+    #[arg(long, env = "TUNO_PROFILE")]
+    pub profile: Option<String>,
+
+    #[command(flatten)]
+    pub gas: GasConfig,
 }
 
+/// Wallet of the commands that never touch the package
 #[derive(Parser)]
-pub struct OwnedKiosk {
-    /// Kiosk's object id on which to place display item
+pub struct WalletArgs {
+    /// The IOTA CLI config file, (default: ~/.iota/iota_config/client.yaml)
+    #[arg(long)]
+    pub config: Option<PathBuf>,
+}
+
+impl WalletArgs {
+    /// Active address of the wallet and its environment, under which profiles are saved
+    pub(crate) fn active(self) -> Result<(IotaAddress, String), Error> {
+        let (_, address, network) = open_wallet(self.config)?;
+        Ok((address, network))
+    }
+}
+
+/// Opens the wallet of the config, with its active address and environment
+fn open_wallet(config: Option<PathBuf>) -> Result<(WalletContext, IotaAddress, String), Error> {
+    let Some(config) = config.or_else(|| {
+        let mut default = dirs::home_dir()?;
+        default.extend([".iota", "iota_config", "client.yaml"]);
+        Some(default)
+    }) else {
+        return Err(Error::Config(
+            "Cannot find wallet config. No config was supplied, and the default path \
+             (~/.iota/iota_config/client.yaml) does not exist.".to_string()
+        ));
+    };
+
+    let wallet = WalletContext::new(&config, None, None).map_err(Error::Wallet)?;
+    let address = wallet.active_address().map_err(Error::Wallet)?;
+    let network = wallet.config.active_env.clone().unwrap_or_else(|| DEFAULT_NETWORK.to_string());
+
+    Ok((wallet, address, network))
+}
+
+#[derive(Parser, Clone)]
+pub struct GasConfig {
+    /// Gas budget (in NANOS) to use instead of estimating it with a dry run
+    #[arg(long)]
+    pub gas_budget: Option<u64>,
+
+    /// Safety multiplier applied to the gas used by the dry run
+    #[arg(long, default_value_t = DEFAULT_GAS_MULTIPLIER)]
+    pub gas_multiplier: f64,
+}
+
+impl Default for GasConfig {
+    fn default() -> Self {
+        Self {
+            gas_budget: None,
+            gas_multiplier: DEFAULT_GAS_MULTIPLIER
+        }
+    }
+}
+
+#[derive(Parser, Default)]
+pub struct KioskArgs {
+    /// Kiosk's object id on which to place display item (default: the profile's, or the only one owned)
     #[arg(long, env = "KIOSK")]
-    kiosk: ObjectID,
+    pub(crate) kiosk: Option<ObjectID>,
 
-    /// Kiosk owner capability's object id
+    /// Kiosk owner capability's object id (default: the profile's, or the one of the kiosk)
     #[arg(long, env = "KIOSK_CAP")]
-    kiosk_cap: ObjectID,
+    pub(crate) kiosk_cap: Option<ObjectID>,
+}
+
+/// Kiosk along with the capability of its owner
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OwnedKiosk {
+    pub(crate) kiosk: ObjectID,
+    pub(crate) kiosk_cap: ObjectID,
 }
 
 impl OwnedKiosk {
@@ -54,30 +148,126 @@ impl OwnedKiosk {
 }
 
 #[derive(Parser)]
-pub struct SongMetadata {
+pub struct Pagination {
+    /// Maximum number of items to list
+    #[arg(long)]
+    pub limit: Option<NonZeroUsize>,
+
+    /// Object id after which to start listing (printed as next cursor by a previous listing)
+    #[arg(long)]
+    pub cursor: Option<ObjectID>,
+}
+
+#[derive(Parser)]
+pub struct SongMetadataArgs {
+    /// Title (default: from the file's tags)
     #[arg(long)]
-    title: String,
-    /// Artist's name
+    title: Option<String>,
+    /// Artist's name (default: from the file's tags)
     #[arg(long)]
-    artist: String,
-    /// Album name
+    artist: Option<String>,
+    /// Album name (default: from the file's tags)
     #[arg(long)]
-    album: String,
-    /// Release year
+    album: Option<String>,
+    /// Release year (default: from the file's tags)
     #[arg(long)]
-    release_year: u64,
-    /// Genre
+    release_year: Option<u64>,
+    /// Genre (default: from the file's tags)
     #[arg(long)]
-    genre: String,
-    /// Url for cover art
+    genre: Option<String>,
+    /// Url for cover art (default: embedded cover art)
     #[arg(long)]
-    cover_art_url: String,
+    cover_art_url: Option<String>,
     /// Price for streaming entire song
     #[arg(long)]
     streaming_price: u64,
 }
 
+impl SongMetadataArgs {
+    /// Completes the metadata given as flags with the file's tags
+    pub(crate) fn resolve(self, tags: TrackTags) -> Result<SongMetadata> {
+        let cover_art_url = tags.cover_art_url();
+
+        Ok(SongMetadata {
+            title: self.title.or(tags.title).context("No title in tags, use --title")?,
+            artist: self.artist.or(tags.artist).context("No artist in tags, use --artist")?,
+            album: self.album.or(tags.album).context("No album in tags, use --album")?,
+            release_year: self.release_year.or(tags.release_year)
+                .context("No release year in tags, use --release-year")?,
+            genre: self.genre.or(tags.genre).context("No genre in tags, use --genre")?,
+            cover_art_url: self.cover_art_url.or(cover_art_url)
+                .context("No usable cover art in tags, use --cover-art-url")?,
+            streaming_price: self.streaming_price,
+        })
+    }
+}
+
+#[derive(Parser)]
+pub struct SongUpdateArgs {
+    /// New title
+    #[arg(long)]
+    title: Option<String>,
+    /// New artist's name
+    #[arg(long)]
+    artist: Option<String>,
+    /// New album name
+    #[arg(long)]
+    album: Option<String>,
+    /// New release year
+    #[arg(long)]
+    release_year: Option<u64>,
+    /// New genre
+    #[arg(long)]
+    genre: Option<String>,
+    /// New url for cover art
+    #[arg(long)]
+    cover_art_url: Option<String>,
+    /// New price for streaming entire song
+    #[arg(long)]
+    streaming_price: Option<u64>,
+}
+
+impl SongUpdateArgs {
+    /// Applies the changes given as flags to the song's current metadata
+    pub(crate) fn apply(self, song: &Song) -> Result<SongMetadata> {
+        if self.title.is_none() && self.artist.is_none() && self.album.is_none()
+            && self.release_year.is_none() && self.genre.is_none()
+            && self.cover_art_url.is_none() && self.streaming_price.is_none()
+        {
+            bail!("Nothing to update, pass at least one of the metadata flags");
+        }
+
+        Ok(SongMetadata {
+            title: self.title.unwrap_or_else(|| song.title.clone()),
+            artist: self.artist.unwrap_or_else(|| song.artist.clone()),
+            album: self.album.unwrap_or_else(|| song.album.clone()),
+            release_year: self.release_year.unwrap_or(song.release_year as u64),
+            genre: self.genre.unwrap_or_else(|| song.genre.clone()),
+            cover_art_url: self.cover_art_url.unwrap_or_else(|| song.cover_art_url.clone()),
+            streaming_price: self.streaming_price.unwrap_or(song.streaming_price as u64),
+        })
+    }
+}
+
+pub struct SongMetadata {
+    pub(crate) title: String,
+    pub(crate) artist: String,
+    pub(crate) album: String,
+    pub(crate) release_year: u64,
+    pub(crate) genre: String,
+    pub(crate) cover_art_url: String,
+    pub(crate) streaming_price: u64,
+}
+
 impl SongMetadata {
+    /// Approximate size of the metadata once serialized as transaction arguments
+    pub(crate) fn args_size(&self) -> usize {
+        [&self.title, &self.artist, &self.album, &self.genre, &self.cover_art_url]
+            .iter()
+            .map(|s| s.len() + 4)
+            .sum::<usize>() + 2 * 8
+    }
+
     pub(crate) fn as_arguments(
         &self,
         ptb: &mut ProgrammableTransactionBuilder
@@ -94,6 +284,7 @@ impl SongMetadata {
     }
 }
 
+#[derive(Serialize, Deserialize, Clone)]
 pub struct CreatorSetup {
     pub cap: ObjectID,
     pub kiosk: ObjectID,
@@ -102,32 +293,75 @@ pub struct CreatorSetup {
 
 pub struct Client {
     wallet: WalletContext,
+    gas: GasConfig,
+    gas_pool: Option<GasPool>,
+    profile: Option<CreatorSetup>,
+    compat: Compatibility,
+    /// Environment of the wallet, under which profiles are saved
+    pub network: String,
     pub address: IotaAddress,
     pub package_id: ObjectID,
 }
 
 impl Client {
-    pub fn new(conn: Connection) -> Result<Self> {
-        let Some(config) = conn.config.or_else(|| {
-            let mut default = dirs::home_dir()?;
-            default.extend([".iota", "iota_config", "client.yaml"]);
-            Some(default)
-        }) else {
-            bail!(
-                "Cannot find wallet config. No config was supplied, and the default path \
-                 (~/.iota/iota_config/client.yaml) does not exist.",
-            );
+    pub fn new(conn: Connection) -> Result<Self, Error> {
+        let (wallet, address, network) = open_wallet(conn.config)?;
+
+        // The active profile is skipped when it belongs to another address, or when the profiles
+        // cannot be read. A selected one is not
+        let profile = match conn.profile {
+            Some(name) => {
+                let profiles = Profiles::load(&network).map_err(Error::Storage)?;
+                let profile = profiles.get(&name).map_err(Error::Other)?;
+                if profile.address != address {
+                    return Err(Error::Other(anyhow!(
+                        "Profile {name} belongs to {}, not to the active address {address}", profile.address
+                    )));
+                }
+
+                Some(profile.setup.clone())
+            },
+            None => match Profiles::load(&network) {
+                Ok(profiles) => profiles.active()
+                    .filter(|(_, p)| p.address == address)
+                    .map(|(_, p)| p.setup.clone()),
+                Err(e) => {
+                    warn!("Active profile ignored, {e:#}");
+                    None
+                }
+            }
         };
 
-        let wallet = WalletContext::new(&config, None, None)?;
-        let address = wallet.active_address()?;
         Ok(Self {
             wallet,
+            gas: conn.gas,
+            gas_pool: None,
+            profile,
+            compat: Compatibility::default(),
+            network,
             address,
             package_id: conn.package_id,
         })
     }
 
+    /// Creates a client and checks which functions of the deployed package it can call.
+    /// Mismatched functions are reported, commands fail only when they call one of them
+    pub async fn connect(conn: Connection) -> Result<Self, Error> {
+        let mut client = Self::new(conn)?;
+        client.compat = check_package(&client.wallet, client.package_id).await?;
+        for issue in client.compat.issues() {
+            warn!("{issue}, commands calling it will fail");
+        }
+
+        Ok(client)
+    }
+
+    /// Name of a function of the package, checked against the deployed one
+    fn function(&self, name: &str) -> Result<Identifier, Error> {
+        self.compat.require(name)?;
+        Ok(Identifier::new(name).unwrap())
+    }
+
     pub(crate) async fn register_creator(
         &self
     ) -> Result<(CreatorSetup, TransactionDigest)> {
@@ -135,8 +369,8 @@ impl Client {
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("register_creator").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(REGISTER_CREATOR_FN)?,
             vec![],
             vec![]
         );
@@ -155,6 +389,112 @@ impl Client {
         ))
     }
 
+    /// Creator capabilities owned by the active address
+    pub(crate) async fn get_creator_caps(&self) -> Result<Vec<CreatorCap>> {
+        let owned = query_owned_objects(
+            &self.wallet,
+            self.address,
+            struct_tag::<MoveCreatorCap>(self.package_id)
+        ).await?;
+
+        let (caps, skipped) = parse_objects::<MoveCreatorCap, CreatorCap>(owned, self.package_id);
+        for e in skipped {
+            warn!("Skipped creator capability: {e}");
+        }
+
+        Ok(caps)
+    }
+
+    /// Kiosk owner capabilities owned by the active address
+    pub(crate) async fn get_kiosk_caps(&self) -> Result<Vec<KioskCap>> {
+        let owned = query_owned_objects(
+            &self.wallet,
+            self.address,
+            struct_tag::<MoveKioskOwnerCap>(self.package_id)
+        ).await?;
+
+        let (caps, skipped) = parse_objects::<MoveKioskOwnerCap, KioskCap>(owned, self.package_id);
+        for e in skipped {
+            warn!("Skipped kiosk owner capability: {e}");
+        }
+
+        Ok(caps)
+    }
+
+    /// Creator capability to use: the given one, the profile's or the only one owned by the active address
+    pub(crate) async fn resolve_creator_cap(&self, cap: Option<ObjectID>) -> Result<ObjectID> {
+        if let Some(cap) = cap.or(self.profile.as_ref().map(|p| p.cap)) {
+            return Ok(cap);
+        }
+
+        match self.get_creator_caps().await?.as_slice() {
+            [cap] => Ok(cap.id),
+            [] => bail!("Active address owns no creator capability, register with `music register`"),
+            caps => bail!(
+                "Active address owns {} creator capabilities ({}), select one with --cap or --profile",
+                caps.len(),
+                caps.iter().map(|c| c.id.to_string()).collect::<Vec<_>>().join(", ")
+            )
+        }
+    }
+
+    /// Completes the kiosk arguments with the profile's kiosk, or with the capabilities owned by
+    /// the active address. `None` when nothing was given and the active address owns no kiosk
+    pub(crate) async fn find_kiosk(&self, args: &KioskArgs) -> Result<Option<OwnedKiosk>> {
+        let (kiosk, kiosk_cap) = match (args.kiosk, args.kiosk_cap, &self.profile) {
+            (None, None, Some(profile)) => (Some(profile.kiosk), Some(profile.kiosk_cap)),
+            (kiosk, kiosk_cap, _) => (kiosk, kiosk_cap)
+        };
+
+        if let (Some(kiosk), Some(kiosk_cap)) = (kiosk, kiosk_cap) {
+            return Ok(Some(OwnedKiosk { kiosk, kiosk_cap }));
+        }
+
+        let caps: Vec<_> = self.get_kiosk_caps().await?
+            .into_iter()
+            .filter(|c| kiosk.is_none_or(|k| c.kiosk == k) && kiosk_cap.is_none_or(|id| c.id == id))
+            .collect();
+
+        match (caps.as_slice(), kiosk.or(kiosk_cap)) {
+            ([cap], _) => Ok(Some(OwnedKiosk { kiosk: cap.kiosk, kiosk_cap: cap.id })),
+            ([], None) => Ok(None),
+            ([], Some(id)) => bail!("Active address owns no capability matching {id}"),
+            (caps, _) => bail!(
+                "Active address owns {} kiosks ({}), select one with --kiosk or --profile",
+                caps.len(),
+                caps.iter().map(|c| c.kiosk.to_string()).collect::<Vec<_>>().join(", ")
+            )
+        }
+    }
+
+    /// Kiosk holding the display of an available song: the given one, else the one in which the
+    /// index last saw the song made available, when the active address owns its capability
+    pub(crate) async fn find_song_kiosk(&self, song: ObjectID, args: &KioskArgs) -> Result<Option<OwnedKiosk>> {
+        if args.kiosk.is_some() || args.kiosk_cap.is_some() {
+            return self.find_kiosk(args).await;
+        }
+
+        let Some(kiosk) = self.synced_index().await?.kiosk_of(&song)? else {
+            return Ok(None);
+        };
+
+        Ok(owned_kiosk(kiosk, self.profile.as_ref(), &self.get_kiosk_caps().await?))
+    }
+
+    /// Like `find_kiosk`, failing when the active address owns no kiosk
+    pub(crate) async fn resolve_kiosk(&self, args: &KioskArgs) -> Result<OwnedKiosk> {
+        self.find_kiosk(args).await?
+            .context("Active address owns no kiosk, register with `music register` or give --kiosk and --kiosk-cap")
+    }
+
+    /// The given kiosk, or the one resolved for the active address
+    pub(crate) async fn kiosk_or_owned(&self, kiosk: Option<ObjectID>) -> Result<ObjectID> {
+        match kiosk {
+            Some(kiosk) => Ok(kiosk),
+            None => Ok(self.resolve_kiosk(&KioskArgs::default()).await?.kiosk)
+        }
+    }
+
     pub(crate) async fn create_song(
         &self,
         cap: ObjectID,
@@ -173,8 +513,8 @@ impl Client {
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("create_song").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(CREATE_SONG_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -189,6 +529,100 @@ impl Client {
         ))
     }
 
+    /// Creates the songs in as few transactions as possible, returning the created
+    /// song of each track in order
+    pub(crate) async fn create_songs(
+        &self,
+        cap: ObjectID,
+        tracks: Vec<(SongMetadata, FileMetadata)>,
+    ) -> Result<Vec<Result<(ObjectID, TransactionDigest)>>> {
+        let mut batches: Vec<Vec<(SongMetadata, FileMetadata)>> = vec![];
+        let mut batch_size = 0;
+        for track in tracks {
+            let size = track.0.args_size() + track.1.args_size();
+            match batches.last_mut() {
+                Some(batch) if batch.len() < MAX_SONGS_PER_BATCH
+                    && batch_size + size <= MAX_BATCH_ARGS_SIZE => {
+                    batch_size += size;
+                    batch.push(track);
+                },
+                _ => {
+                    batch_size = size;
+                    batches.push(vec![track]);
+                }
+            }
+        }
+
+        let mut created = vec![];
+        for batch in batches {
+            let len = batch.len();
+            match self.create_songs_batch(cap, batch).await {
+                Ok((songs, digest)) => {
+                    info!("Created {} song(s) [{}]", songs.len(), digest);
+                    created.extend(songs.into_iter().map(|song| Ok((song, digest))));
+                },
+                Err(e) => {
+                    error!("Could not create batch of {len} song(s): {e}");
+                    created.extend((0..len).map(|_| Err(anyhow::anyhow!("{e}"))));
+                }
+            }
+        }
+
+        Ok(created)
+    }
+
+    async fn create_songs_batch(
+        &self,
+        cap: ObjectID,
+        tracks: Vec<(SongMetadata, FileMetadata)>,
+    ) -> Result<(Vec<ObjectID>, TransactionDigest)> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let cap = ptb.obj(ObjectArg::ImmOrOwnedObject(
+            self.wallet.get_object_ref(cap).await?
+        ))?;
+
+        for (song_md, file_md) in &tracks {
+            let mut args = song_md.as_arguments(&mut ptb)?;
+            args.append(&mut file_md.as_arguments(&mut ptb)?);
+            args.push(cap);
+
+            ptb.programmable_move_call(
+                self.package_id,
+                Identifier::new(TUNO_MODULE).unwrap(),
+                self.function(CREATE_SONG_FN)?,
+                vec![get_usdc_type_tag()?],
+                args
+            );
+        }
+
+        let resp = self.build_and_execute_transaction_data(
+            ptb.finish()
+        ).await?;
+
+        let songs = extract_created_songs(&resp, self.package_id)?;
+        if songs.len() != tracks.len() {
+            bail!("{} song(s) created out of {} [{}]", songs.len(), tracks.len(), resp.digest);
+        }
+
+        Ok((songs, resp.digest))
+    }
+
+    /// Makes the songs available in sequential batches, as each uses the kiosk owner capability, returning each song's outcome in order
+    pub(crate) async fn make_songs_available(
+        &self,
+        songs: &[ObjectID],
+        owned_kiosk: &OwnedKiosk
+    ) -> Vec<(ObjectID, Result<TransactionDigest>)> {
+        let mut available = vec![];
+        for batch in songs.chunks(MAX_SONGS_PER_BATCH) {
+            available.append(
+                &mut self.call_on_songs(batch, MAKE_SONG_AVAILABLE_FN, Some(owned_kiosk), &|_| Ok(vec![])).await
+            );
+        }
+
+        available
+    }
+
     pub(crate) async fn make_song_available(
         &self,
         song: ObjectID,
@@ -202,8 +636,8 @@ impl Client {
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("make_song_available").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(MAKE_SONG_AVAILABLE_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -216,21 +650,18 @@ impl Client {
         )
     }
 
-    pub(crate) async fn make_song_unavailable(
+    pub(crate) async fn update_song(
         &self,
         song: ObjectID,
-        owned_kiosk: OwnedKiosk
+        song_md: &SongMetadata
     ) -> Result<TransactionDigest> {
         let mut ptb = ProgrammableTransactionBuilder::new();
-        let mut args = vec![
-            ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?
-        ];
-        args.append(&mut owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?);
-
+        let mut args = vec![ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?];
+        args.append(&mut song_md.as_arguments(&mut ptb)?);
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("make_song_unavailable").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(UPDATE_SONG_METADATA_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -242,44 +673,45 @@ impl Client {
         )
     }
 
-    pub(crate) async fn distribute_all(
+    /// Copies the song's metadata to its display in the kiosk
+    pub(crate) async fn refresh_song_display(
         &self,
-        url: &str,
-        streaming_price: usize
-    ) -> Result<Vec<ObjectID>> {
-        let mut distributing = vec![];
-        for song_id in get_all_song_ids()? {
-            let song = ObjectID::from_hex_literal(&song_id)?;
-            match self.distribute(song, url, streaming_price).await {
-                Ok(digest) => {
-                    distributing.push(song);
-                    info!("Registered to distribute {} [{}]", song_id, digest)
-                },
-                Err(e) => error!("Could not register to {}: {}", song_id, e)
-            }
-        }
+        song: ObjectID,
+        owned_kiosk: &OwnedKiosk
+    ) -> Result<TransactionDigest> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let mut args = vec![ptb.obj(get_shared_object_ref(song, false, &self.wallet).await?)?];
+        args.append(&mut owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?);
+        ptb.programmable_move_call(
+            self.package_id,
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(REFRESH_SONG_DISPLAY_FN)?,
+            vec![get_usdc_type_tag()?],
+            args
+        );
 
-        Ok(distributing)
+        Ok(
+            self.build_and_execute_transaction_data(
+                ptb.finish()
+            ).await?.digest
+        )
     }
 
-    async fn distribute(
+    pub(crate) async fn make_song_unavailable(
         &self,
         song: ObjectID,
-        url: &str,
-        streaming_price: usize
+        owned_kiosk: OwnedKiosk
     ) -> Result<TransactionDigest> {
         let mut ptb = ProgrammableTransactionBuilder::new();
-        let args = vec![
-            ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?,
-            ptb.pure(url)?,
-            ptb.pure(streaming_price)?
-
+        let mut args = vec![
+            ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?
         ];
+        args.append(&mut owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?);
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("register_as_distributor").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(MAKE_SONG_UNAVAILABLE_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -291,38 +723,131 @@ impl Client {
         )
     }
 
+    pub(crate) async fn distribute_all(
+        &self,
+        url: &str,
+        streaming_price: usize
+    ) -> Result<Vec<ObjectID>> {
+        let songs = get_all_song_ids()?
+            .iter()
+            .map(|song_id| ObjectID::from_hex_literal(song_id))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let register_args = |ptb: &mut ProgrammableTransactionBuilder| -> Result<Vec<Argument>> {
+            Ok(vec![ptb.pure(url)?, ptb.pure(streaming_price)?])
+        };
+
+        Ok(
+            stream::iter(songs.chunks(MAX_SONGS_PER_BATCH))
+                .map(|batch| self.call_on_songs(batch, REGISTER_AS_DISTRIBUTOR_FN, None, &register_args))
+                .buffer_unordered(self.concurrency())
+                .concat()
+                .await
+                .into_iter()
+                .filter_map(|(song, called)| called.ok().map(|_| song))
+                .collect()
+        )
+    }
+
     pub(crate) async fn undistribute_all(&self) -> Result<Vec<ObjectID>> {
-        let mut undistributed = vec![];
-        for song_id in get_all_song_ids()? {
-            let song = ObjectID::from_hex_literal(&song_id)?;
-            match self.undistribute(song).await {
-                Ok(digest) => {
-                    undistributed.push(song);
-                    info!("unregistered on {} [{}]", song_id, digest);
-                },
-                Err(e) => error!("Could not unregister on {}: {}", song_id, e)
-            }
-        }
+        let songs = get_all_song_ids()?
+            .iter()
+            .map(|song_id| ObjectID::from_hex_literal(song_id))
+            .collect::<Result<Vec<_>, _>>()?;
 
-        Ok(undistributed)
+        Ok(
+            stream::iter(songs.chunks(MAX_SONGS_PER_BATCH))
+                .map(|batch| self.call_on_songs(batch, REMOVE_AS_DISTRIBUTOR_FN, None, &|_| Ok(vec![])))
+                .buffer_unordered(self.concurrency())
+                .concat()
+                .await
+                .into_iter()
+                .filter_map(|(song, called)| called.ok().map(|_| song))
+                .collect()
+        )
     }
 
     pub(crate) async fn undistribute(
         &self,
         song: ObjectID
     ) -> Result<TransactionDigest> {
+        self.try_call_on_songs(&[song], REMOVE_AS_DISTRIBUTOR_FN, None, &|_| Ok(vec![])).await
+    }
+
+    /// Calls `function` on every song within a single transaction, falling back to one
+    /// transaction per song when the batch fails so that a single song cannot hold back the others.
+    /// Returns the outcome of the call on each song
+    async fn call_on_songs<F>(
+        &self,
+        songs: &[ObjectID],
+        function: &str,
+        owned_kiosk: Option<&OwnedKiosk>,
+        extra_args: &F
+    ) -> Vec<(ObjectID, Result<TransactionDigest>)>
+    where
+        F: Fn(&mut ProgrammableTransactionBuilder) -> Result<Vec<Argument>>
+    {
+        let e = match self.try_call_on_songs(songs, function, owned_kiosk, extra_args).await {
+            Ok(digest) => {
+                info!("Called {function} on {} song(s) [{digest}]", songs.len());
+                return songs.iter().map(|song| (*song, Ok(digest))).collect();
+            },
+            Err(e) => e
+        };
+
+        if let [song] = songs {
+            error!("Could not call {function} on {song}: {e}");
+            return vec![(*song, Err(e))];
+        }
+
+        warn!("Batch {function} on {} song(s) failed, retrying individually: {e}", songs.len());
+        let mut called = vec![];
+        for song in songs {
+            let called_on = self.try_call_on_songs(&[*song], function, owned_kiosk, extra_args).await;
+            match &called_on {
+                Ok(digest) => info!("Called {function} on {song} [{digest}]"),
+                Err(e) => error!("Could not call {function} on {song}: {e}")
+            }
+
+            called.push((*song, called_on));
+        }
+
+        called
+    }
+
+    /// Calls `function` on every song, passing the kiosk and its owner capability after the song
+    /// when given, then the extra arguments
+    async fn try_call_on_songs<F>(
+        &self,
+        songs: &[ObjectID],
+        function: &str,
+        owned_kiosk: Option<&OwnedKiosk>,
+        extra_args: &F
+    ) -> Result<TransactionDigest>
+    where
+        F: Fn(&mut ProgrammableTransactionBuilder) -> Result<Vec<Argument>>
+    {
         let mut ptb = ProgrammableTransactionBuilder::new();
-        let args = vec![
-            ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?
-        ];
+        let kiosk_args = match owned_kiosk {
+            Some(owned_kiosk) => owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?,
+            None => vec![]
+        };
 
-        ptb.programmable_move_call(
-            self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("remove_as_distributor").unwrap(),
-            vec![get_usdc_type_tag()?],
-            args
-        );
+        for song in songs {
+            let mut args = vec![
+                ptb.obj(get_shared_object_ref(*song, true, &self.wallet).await?)?
+            ];
+            args.extend(&kiosk_args);
+            args.append(&mut extra_args(&mut ptb)?);
+
+            ptb.programmable_move_call(
+                self.package_id,
+                Identifier::new(TUNO_MODULE).unwrap(),
+                self.function(function)?,
+                vec![get_usdc_type_tag()?],
+                args
+            );
+        }
 
         Ok(
             self.build_and_execute_transaction_data(
@@ -335,13 +860,87 @@ impl Client {
         &self,
         song: ObjectID,
         distributor: &IotaAddress
+    ) -> Result<Transaction, Error> {
+        Ok(self.build_payment_transaction(song, distributor).await?)
+    }
+
+    /// Pays a distributor of the song and streams the song from them, checking every chunk
+    /// against the song's signature. `on_data` is given each verified chunk and the song's length
+    pub(crate) async fn stream_song<F>(&self, song: ObjectID, mut on_data: F) -> Result<()>
+    where
+        F: FnMut(&[u8], usize)
+    {
+        let mut obj = self.get_song(song).await?;
+        let Some((address, distributor)) = obj.distributors.get_first() else {
+            bail!("Song {song} has no distributor");
+        };
+
+        let tx = self.build_payment_transaction(song, address).await?;
+        let mut channel = pb::tuno_client::TunoClient::connect(distributor.url.clone()).await?;
+
+        let mut stream = channel.stream_song(
+            pb::SongStreamRequest {
+                raw_transaction: hex::encode(bcs::to_bytes(&tx)?),
+                block_size: 4 * TUNO_BASE_CHUNK_SIZE as u32,
+            }
+        ).await?
+        .into_inner();
+
+        let mut received = 0;
+        while let Some(item) = stream.next().await {
+            match obj.signature.consume_data(item?.data) {
+                Some(d) => {
+                    received += d.len();
+                    on_data(&d, obj.length)
+                },
+                None => return Err(Error::Verification("received data does not match the song's signature".to_string()).into())
+            };
+        }
+
+        if received != obj.length {
+            return Err(Error::Verification(format!(
+                "received {received} bytes, the song is {} bytes long", obj.length
+            )).into());
+        }
+
+        Ok(())
+    }
+
+    /// Streams the whole song in memory. `progress` is given the bytes received so far and the song's length
+    pub(crate) async fn download_song<F>(&self, song: ObjectID, mut progress: F) -> Result<Vec<u8>>
+    where
+        F: FnMut(usize, usize)
+    {
+        let mut data: Vec<u8> = vec![];
+        self.stream_song(song, |chunk, length| {
+            data.extend_from_slice(chunk);
+            progress(data.len(), length);
+        }).await?;
+
+        Ok(data)
+    }
+
+    async fn build_payment_transaction(
+        &self,
+        song: ObjectID,
+        distributor: &IotaAddress
     ) -> Result<Transaction> {
         let price = self.get_total_price(song, distributor).await?;
 
         let mut ptb = ProgrammableTransactionBuilder::new();
-        let coin = ptb.obj(ObjectArg::ImmOrOwnedObject(
-            self.get_payment_coin(price).await?
-        ))?;
+        let mut coins = self.get_payment_coins(price).await?.into_iter();
+        let Some(primary) = coins.next() else {
+            bail!("No coin available");
+        };
+
+        let coin = ptb.obj(ObjectArg::ImmOrOwnedObject(primary))?;
+        let merged = coins
+            .map(|c| ptb.obj(ObjectArg::ImmOrOwnedObject(c)))
+            .collect::<Result<Vec<_>>>()?;
+        if !merged.is_empty() {
+            ptb.command(Command::MergeCoins(coin, merged));
+        }
+
         let amounts = vec![ptb.pure(price)?];
         let args = vec![
             ptb.obj(get_shared_object_ref(song, true, &self.wallet).await?)?,
@@ -351,8 +950,8 @@ impl Client {
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("pay_royalties").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(PAY_ROYALTIES_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -377,8 +976,8 @@ impl Client {
 
         ptb.programmable_move_call(
             self.package_id,
-            Identifier::new("tuno").unwrap(),
-            Identifier::new("get_total_price").unwrap(),
+            Identifier::new(TUNO_MODULE).unwrap(),
+            self.function(GET_TOTAL_PRICE_FN)?,
             vec![get_usdc_type_tag()?],
             args
         );
@@ -408,97 +1007,395 @@ impl Client {
         Ok(usize::from_le_bytes(res.as_slice().try_into()?))
     }
 
-    pub(crate) async fn get_all_owned_songs(&self) -> Result<SongList> {
-        let songs = query_owned_songs(&self.wallet, self.package_id).await?
-            .into_iter()
-            .map(|obj| obj.data.unwrap().content.unwrap())
-            .map(|content| match content {
-                IotaParsedData::MoveObject(o) => Song::from(o.fields),
-                _ => panic!("IOTA Object Response could not be parsed")
-            }).collect();
+    pub(crate) async fn get_all_owned_songs(
+        &self,
+        pagination: &Pagination
+    ) -> Result<(SongList, Vec<ParseError>, Option<ObjectID>)> {
+        let (owned, next_cursor) = self.synced_index().await?
+            .songs_by_owner(&self.address, pagination)?;
+        let owned = owned.into_iter().map(|s| s.id).collect();
+
+        let (songs, skipped) = parse_objects::<MoveSong, Song>(query_objects(&self.wallet, owned).await?, self.package_id);
+        let songs = songs.into_iter()
+            .filter(|song| song.owner == self.address)
+            .collect();
+
+        Ok((songs, skipped, next_cursor))
+    }
+
+    /// Opens the local event index of the package and brings it up to date
+    pub async fn synced_index(&self) -> Result<Indexer, Error> {
+        let index = Indexer::open(self.package_id).map_err(Error::Storage)?;
+        let indexed = index.sync(&self.wallet).await?;
+        trace!("Indexed {indexed} new event(s)");
 
-        Ok(songs)
+        Ok(index)
     }
 
-    pub(crate) async fn get_kiosk_songs(&self, kiosk: ObjectID) -> Result<SongDisplayList> {
-        let songs = query_kiosk_songs(&self.wallet, kiosk).await?
-            .into_iter()
-            .map(|obj| obj.data.unwrap().content.unwrap())
-            .map(|content| match content {
-                IotaParsedData::MoveObject(o) => SongDisplay::from(o.fields),
-                _ => panic!("IOTA Object Response could not be parsed")
-            }).collect();
+    pub(crate) async fn run_indexer(&self, interval: Duration) -> Result<()> {
+        Indexer::open(self.package_id)?
+            .run(&self.wallet, interval).await
+    }
+
+    pub(crate) async fn get_kiosk_songs(
+        &self,
+        kiosk: ObjectID,
+        pagination: &Pagination
+    ) -> Result<(SongDisplayList, Vec<ParseError>, Option<ObjectID>)> {
+        let (displays, next_cursor) = query_kiosk_songs(&self.wallet, kiosk, pagination).await?;
+        let (songs, skipped) = parse_objects::<MoveSongDisplay, SongDisplay>(displays, self.package_id);
 
-        Ok(songs)
+        Ok((songs.into_iter().collect(), skipped, next_cursor))
     }
 
-    pub async fn get_song(&self, song: ObjectID) -> Result<Song> {
-        let Some(data) = query_object(&self.wallet, song).await?.data else {
-            bail!("Song's data could not be found");
+    pub(crate) async fn get_kiosk(&self, kiosk: ObjectID) -> Result<KioskInfo> {
+        Ok(KioskInfo::from(parse_object::<MoveKiosk>(query_object(&self.wallet, kiosk).await?, self.package_id)?))
+    }
+
+    /// Displays of the kiosk whose song no longer exists, or no longer refers to them
+    pub(crate) async fn get_orphan_displays(&self, kiosk: ObjectID) -> Result<Vec<SongDisplay>> {
+        let pagination = Pagination { limit: None, cursor: None };
+        let (displays, _, _) = self.get_kiosk_songs(kiosk, &pagination).await?;
+
+        let song_ids = displays.0.iter().map(|d| d.song_id).collect();
+        let (songs, _) = parse_objects::<MoveSong, Song>(query_objects(&self.wallet, song_ids).await?, self.package_id);
+        let listed: HashSet<_> = songs.into_iter()
+            .filter_map(|song| song.display_id.map(|display| (song.id, display)))
+            .collect();
+
+        Ok(
+            displays.0.into_iter()
+                .filter(|d| !listed.contains(&(d.song_id, d.id)))
+                .collect()
+        )
+    }
+
+    /// Withdraws the kiosk's profits to the active address, all of them unless given an amount
+    pub(crate) async fn withdraw_kiosk_profits(
+        &self,
+        owned_kiosk: &OwnedKiosk,
+        amount: Option<u64>
+    ) -> Result<TransactionDigest> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let mut args = owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?;
+        args.push(ptb.pure(amount)?);
+
+        let coin = ptb.programmable_move_call(
+            IOTA_FRAMEWORK_PACKAGE_ID,
+            Identifier::new(KIOSK_MODULE).unwrap(),
+            Identifier::new(KIOSK_WITHDRAW_FN).unwrap(),
+            vec![],
+            args
+        );
+        ptb.transfer_arg(self.address, coin);
+
+        Ok(
+            self.build_and_execute_transaction_data(
+                ptb.finish()
+            ).await?.digest
+        )
+    }
+
+    /// Takes an item out of the kiosk: song displays are deleted, provided that their song no longer
+    /// refers to them, and other items are sent to the active address
+    pub(crate) async fn remove_kiosk_item(
+        &self,
+        owned_kiosk: &OwnedKiosk,
+        item: ObjectID
+    ) -> Result<TransactionDigest> {
+        let Some(IotaRawData::MoveObject(raw)) = query_object(&self.wallet, item).await?
+            .data
+            .and_then(|data| data.bcs)
+        else {
+            bail!("Item {item} is not a Move object");
         };
 
-        let Some(IotaParsedData::MoveObject(o)) = data.content else {
-            bail!("IOTA Object Response could not be parsed")
+        let mut ptb = ProgrammableTransactionBuilder::new();
+
+        if raw.type_ == struct_tag::<MoveSongDisplay>(self.package_id) {
+            let orphans = self.get_orphan_displays(owned_kiosk.kiosk).await?;
+            let Some(display) = orphans.iter().find(|d| d.id == item) else {
+                bail!("Display {item} belongs to an existing song, make the song unavailable instead");
+            };
+
+            let mut args = vec![ptb.obj(get_shared_object_ref(display.song_id, false, &self.wallet).await?)?];
+            args.append(&mut owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?);
+            args.push(ptb.pure(item)?);
+
+            ptb.programmable_move_call(
+                self.package_id,
+                Identifier::new(TUNO_MODULE).unwrap(),
+                self.function(REMOVE_SONG_DISPLAY_FN)?,
+                raw.type_.type_params,
+                args
+            );
+        } else {
+            let mut args = owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?;
+            args.push(ptb.pure(item)?);
+
+            let taken = ptb.programmable_move_call(
+                IOTA_FRAMEWORK_PACKAGE_ID,
+                Identifier::new(KIOSK_MODULE).unwrap(),
+                Identifier::new(KIOSK_TAKE_FN).unwrap(),
+                vec![TypeTag::Struct(Box::new(raw.type_))],
+                args
+            );
+            ptb.transfer_arg(self.address, taken);
+        }
+
+        Ok(
+            self.build_and_execute_transaction_data(
+                ptb.finish()
+            ).await?.digest
+        )
+    }
+
+    /// Hands the kiosk over to another address, along with its owner capability
+    pub(crate) async fn transfer_kiosk_cap(
+        &self,
+        owned_kiosk: &OwnedKiosk,
+        recipient: IotaAddress
+    ) -> Result<TransactionDigest> {
+        let mut ptb = ProgrammableTransactionBuilder::new();
+        let args = owned_kiosk.as_arguments(&self.wallet, &mut ptb).await?;
+        let cap = args[1];
+
+        let mut set_owner_args = args;
+        set_owner_args.push(ptb.pure(recipient)?);
+        ptb.programmable_move_call(
+            IOTA_FRAMEWORK_PACKAGE_ID,
+            Identifier::new(KIOSK_MODULE).unwrap(),
+            Identifier::new(KIOSK_SET_OWNER_CUSTOM_FN).unwrap(),
+            vec![],
+            set_owner_args
+        );
+        ptb.transfer_arg(recipient, cap);
+
+        Ok(
+            self.build_and_execute_transaction_data(
+                ptb.finish()
+            ).await?.digest
+        )
+    }
+
+    pub async fn get_song(&self, song: ObjectID) -> Result<Song, Error> {
+        Ok(Song::from(parse_object::<MoveSong>(query_object(&self.wallet, song).await?, self.package_id)?))
+    }
+
+    /// Coins to merge, the first one being the one to split the payment from
+    pub(crate) async fn get_payment_coins(&self, min_amount: usize) -> Result<Vec<ObjectRef>> {
+        let coins = query_usdc_coins(self.address, &self.wallet).await?;
+        let available: u64 = coins.iter().map(|c| c.balance).sum();
+
+        let Some(selected) = select_coins(coins, min_amount as u64) else {
+            bail!("No coin available: {available} USDC owned, {min_amount} required");
         };
 
-        Ok(Song::from(o.fields))
+        Ok(selected.iter().map(|c| c.object_ref()).collect())
     }
 
-    pub(crate) async fn get_payment_coin(&self, min_amount: usize) -> Result<ObjectRef> {
-        for c in query_usdc_coins(self.address, &self.wallet).await? {
-            let Some(data) = c.data else {
-                continue;
+    /// Merges every USDC coin of the active address into the largest one
+    pub(crate) async fn consolidate_usdc_coins(
+        &self
+    ) -> Result<Option<(ObjectID, Vec<TransactionDigest>)>> {
+        let mut coins = query_usdc_coins(self.address, &self.wallet).await?;
+        coins.sort_by_key(|c| std::cmp::Reverse(c.balance));
+
+        let Some((primary, rest)) = coins.split_first() else {
+            return Ok(None);
+        };
+
+        if rest.is_empty() {
+            return Ok(None);
+        }
+
+        let mut digests = vec![];
+        for chunk in rest.chunks(MAX_MERGE_COINS_PER_TX) {
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            let coin = ptb.obj(ObjectArg::ImmOrOwnedObject(
+                self.wallet.get_object_ref(primary.coin_object_id).await?
+            ))?;
+            let merged = chunk.iter()
+                .map(|c| ptb.obj(ObjectArg::ImmOrOwnedObject(c.object_ref())))
+                .collect::<Result<Vec<_>>>()?;
+
+            ptb.command(Command::MergeCoins(coin, merged));
+
+            let resp = self.build_and_execute_transaction_data(ptb.finish()).await?;
+            info!("Merged {} coin(s) into {} [{}]", chunk.len(), primary.coin_object_id, resp.digest);
+            digests.push(resp.digest);
+        }
+
+        Ok(Some((primary.coin_object_id, digests)))
+    }
+
+    /// Splits the IOTA of the active address into `size` gas coins leased to concurrent transactions
+    pub(crate) async fn init_gas_pool(&mut self, size: usize) -> Result<()> {
+        let mut gas_coins = query_gas_coins(self.address, &self.wallet).await?;
+        if gas_coins.len() < size {
+            let Some(largest) = gas_coins.iter().max_by_key(|c| c.balance) else {
+                bail!("No gas coin owned by {}", self.address);
             };
 
-            let Some(IotaParsedData::MoveObject(o)) = &data.content else {
-                continue;
+            let missing = size - gas_coins.len();
+            let share = largest.balance / (missing as u64 + 1);
+
+            let mut ptb = ProgrammableTransactionBuilder::new();
+            let amounts = (0..missing)
+                .map(|_| ptb.pure(share))
+                .collect::<Result<Vec<_>>>()?;
+            let Argument::Result(split) = ptb.command(
+                Command::SplitCoins(Argument::GasCoin, amounts)
+            ) else {
+                bail!("Could not split gas coin");
             };
 
-            if let Some(IotaMoveValue::String(b)) = o.fields.read_dynamic_field_value("balance") {
-                if b.parse::<usize>().unwrap() >= min_amount {
-                    return Ok(data.object_ref());
-                }
-            }
+            ptb.transfer_args(
+                self.address,
+                (0..missing).map(|i| Argument::NestedResult(split, i as u16)).collect()
+            );
+
+            let tx = self.build_and_sign_with_gas(
+                ptb.finish(),
+                Some(largest.coin_object_id)
+            ).await?;
+            let resp = self.execute_transaction(tx).await?;
+            info!("Split {} gas coin(s) of {share} NANOS [{}]", missing, resp.digest);
+
+            gas_coins = query_gas_coins(self.address, &self.wallet).await?;
         }
 
-        bail!("No coin available")
+        gas_coins.sort_by_key(|c| std::cmp::Reverse(c.balance));
+        let pool = GasPool::new(
+            gas_coins.into_iter().take(size).map(|c| c.coin_object_id).collect()
+        ).with_context(|| format!("No gas coin owned by {}", self.address))?;
+        self.gas_pool = Some(pool);
+
+        Ok(())
+    }
+
+    /// Number of transactions that can be executed concurrently by the active address
+    fn concurrency(&self) -> usize {
+        self.gas_pool.as_ref().map_or(1, |pool| pool.size())
     }
 
     pub(crate) async fn build_and_sign_transaction_data(
         &self,
         pt: ProgrammableTransaction
+    ) -> Result<Transaction> {
+        self.build_and_sign_with_gas(pt, None).await
+    }
+
+    /// Builds and signs the transaction paying gas with `gas` if given, or with the best fitting gas coin
+    async fn build_and_sign_with_gas(
+        &self,
+        pt: ProgrammableTransaction,
+        gas: Option<ObjectID>
     ) -> Result<Transaction> {
         trace!("building transaction: \n{}", pt.to_string());
         let sender = self.address;
+        let gas_price = self.wallet.get_reference_gas_price().await?;
+        let mut gas_coins = query_gas_coins(sender, &self.wallet).await?;
+        if let Some(gas) = gas {
+            gas_coins.retain(|c| c.coin_object_id == gas);
+        }
+
+        let budget = match self.gas.gas_budget {
+            Some(budget) => budget,
+            None => self.estimate_gas_budget(&pt, &gas_coins, gas_price).await?
+        };
+
+        let gas = select_gas_coin(gas_coins, budget)?;
         let tx_data = TransactionData::new_programmable(
             sender,
-            self.wallet.get_all_gas_objects_owned_by_address(sender).await?,
+            vec![gas.object_ref()],
             pt,
-            50_000_000,
-            self.wallet.get_reference_gas_price().await?,
+            budget,
+            gas_price,
         );
 
-        trace!("Signing {}...", tx_data.digest());
+        trace!("Signing {} (gas budget: {budget})...", tx_data.digest());
         Ok(self.wallet.sign_transaction(&tx_data))
     }
 
+    /// Dry runs the transaction and applies the safety multiplier to the gas it used
+    async fn estimate_gas_budget(
+        &self,
+        pt: &ProgrammableTransaction,
+        gas_coins: &[Coin],
+        gas_price: u64
+    ) -> Result<u64> {
+        let Some(gas) = gas_coins.iter().max_by_key(|c| c.balance) else {
+            bail!("No gas coin owned by {}", self.address);
+        };
+
+        let tx_data = TransactionData::new_programmable(
+            self.address,
+            vec![gas.object_ref()],
+            pt.clone(),
+            gas.balance.min(MAX_GAS_BUDGET),
+            gas_price,
+        );
+
+        let dry_run = self.wallet.get_client().await?
+            .read_api()
+            .dry_run_transaction_block(tx_data).await?;
+
+        if let IotaExecutionStatus::Failure { error } = dry_run.effects.status() {
+            if let Some(error) = TunoError::from_execution_error(error, self.package_id) {
+                return Err(Error::Abort { error, digest: None }.into());
+            }
+            bail!("Dry run failed: {error}");
+        }
+
+        let summary = dry_run.effects.gas_cost_summary();
+        let used = summary.computation_cost + summary.storage_cost;
+        let budget = (used as f64 * self.gas.gas_multiplier).ceil() as u64;
+        trace!("Estimated gas budget: {budget} ({used} used by dry run)");
+
+        Ok(budget)
+    }
+
     async fn build_and_execute_transaction_data(
         &self,
         pt: ProgrammableTransaction
     ) -> Result<IotaTransactionBlockResponse> {
-        let tx: Transaction = self.build_and_sign_transaction_data(pt).await?;
+        let lease = match &self.gas_pool {
+            Some(pool) => Some(pool.lease().await?),
+            None => None
+        };
+
+        let tx: Transaction = self.build_and_sign_with_gas(
+            pt,
+            lease.as_ref().map(|l| l.coin())
+        ).await?;
 
         trace!("Executing {}...", tx.digest());
         self.execute_transaction(tx).await
     }
 
+    /// Executes a listener's payment, signed by the listener and paid with their gas. Shares the
+    /// client with the transactions leasing pooled gas coins, so payments never wait on the pool
+    pub(crate) async fn execute_payment(
+        &self,
+        tx: Transaction
+    ) -> Result<IotaTransactionBlockResponse> {
+        if let Some(pool) = &self.gas_pool {
+            if tx.transaction_data().gas().iter().any(|(coin, _, _)| pool.contains(coin)) {
+                bail!("Payment is paid with a pooled gas coin of the distributor");
+            }
+        }
+
+        self.execute_transaction(tx).await
+    }
+
     pub(crate) async fn execute_transaction(
         &self,
         tx: Transaction
     ) -> Result<IotaTransactionBlockResponse> {
         let response = match self.wallet.execute_transaction_may_fail(tx).await {
             Ok(res) => res,
-            Err(e) => bail!("Error executing tx: {e}")
+            Err(e) => return Err(Error::Rpc(e).into())
         };
     
         let Some(effects) = &response.effects else {
@@ -506,10 +1403,70 @@ impl Client {
         };
     
         if let IotaExecutionStatus::Failure { error } = effects.status() {
-            bail!("Error {}, executing {}", error.to_owned(), response.digest);
+            if let Some(error) = TunoError::from_execution_error(error, self.package_id) {
+                return Err(Error::Abort { error, digest: Some(response.digest) }.into());
+            }
+
+            return Err(Error::Execution { digest: response.digest, status: error.to_owned() }.into());
         }
     
         Ok(response)
     }
 
 }
+
+/// The kiosk with the capability held for it, by the profile or else among the owned ones
+fn owned_kiosk(kiosk: ObjectID, profile: Option<&CreatorSetup>, caps: &[KioskCap]) -> Option<OwnedKiosk> {
+    let kiosk_cap = profile.filter(|p| p.kiosk == kiosk).map(|p| p.kiosk_cap)
+        .or_else(|| caps.iter().find(|c| c.kiosk == kiosk).map(|c| c.id))?;
+
+    Some(OwnedKiosk { kiosk, kiosk_cap })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn setup(kiosk: ObjectID) -> CreatorSetup {
+        CreatorSetup { cap: ObjectID::random(), kiosk, kiosk_cap: ObjectID::random() }
+    }
+
+    #[test]
+    fn owned_kiosk_prefers_the_profile() {
+        let kiosk = ObjectID::random();
+        let profile = setup(kiosk);
+        let caps = [KioskCap { id: ObjectID::random(), kiosk }];
+
+        assert_eq!(
+            owned_kiosk(kiosk, Some(&profile), &caps),
+            Some(OwnedKiosk { kiosk, kiosk_cap: profile.kiosk_cap })
+        );
+    }
+
+    #[test]
+    fn owned_kiosk_falls_back_to_the_owned_caps() {
+        let kiosk = ObjectID::random();
+        let profile = setup(ObjectID::random());
+        let caps = [
+            KioskCap { id: ObjectID::random(), kiosk: ObjectID::random() },
+            KioskCap { id: ObjectID::random(), kiosk }
+        ];
+
+        assert_eq!(
+            owned_kiosk(kiosk, Some(&profile), &caps),
+            Some(OwnedKiosk { kiosk, kiosk_cap: caps[1].id })
+        );
+        assert_eq!(
+            owned_kiosk(kiosk, None, &caps),
+            Some(OwnedKiosk { kiosk, kiosk_cap: caps[1].id })
+        );
+    }
+
+    #[test]
+    fn owned_kiosk_is_none_for_a_kiosk_of_someone_else() {
+        let profile = setup(ObjectID::random());
+        let caps = [KioskCap { id: ObjectID::random(), kiosk: profile.kiosk }];
+
+        assert_eq!(owned_kiosk(ObjectID::random(), Some(&profile), &caps), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::collections::BTreeMap;
+
+use log::trace;
+
+use iota_sdk::rpc_types::{IotaMoveNormalizedFunction, IotaMoveNormalizedType};
+use iota_sdk::types::base_types::ObjectID;
+use iota_sdk::wallet_context::WalletContext;
+
+use crate::constants::{
+    TUNO_MODULE, CREATE_SONG_FN, GET_TOTAL_PRICE_FN, MAKE_SONG_AVAILABLE_FN, MAKE_SONG_UNAVAILABLE_FN,
+    PAY_ROYALTIES_FN, REFRESH_SONG_DISPLAY_FN, REGISTER_AS_DISTRIBUTOR_FN, REGISTER_CREATOR_FN,
+    REMOVE_AS_DISTRIBUTOR_FN, REMOVE_SONG_DISPLAY_FN, UPDATE_SONG_METADATA_FN
+};
+use crate::errors::Error;
+
+/// Parameters of every function the client calls, as rendered by `describe`
+const EXPECTED_FUNCTIONS: &[(&str, &[&str])] = &[
+    (REGISTER_CREATOR_FN, &["&mut tx_context::TxContext"]),
+    (CREATE_SONG_FN, &[
+        "vector<u8>", "vector<u8>", "vector<u8>", "u64", "vector<u8>", "vector<u8>",
+        "u64", "u64", "u64", "vector<vector<u8>>", "&tuno::CreatorCap", "&mut tx_context::TxContext"
+    ]),
+    (MAKE_SONG_AVAILABLE_FN, &[
+        "&mut tuno::Song<T0>", "&mut kiosk::Kiosk", "&kiosk::KioskOwnerCap", "&mut tx_context::TxContext"
+    ]),
+    (MAKE_SONG_UNAVAILABLE_FN, &[
+        "&mut tuno::Song<T0>", "&mut kiosk::Kiosk", "&kiosk::KioskOwnerCap", "&mut tx_context::TxContext"
+    ]),
+    (REGISTER_AS_DISTRIBUTOR_FN, &[
+        "&mut tuno::Song<T0>", "vector<u8>", "u64", "&mut tx_context::TxContext"
+    ]),
+    (REMOVE_AS_DISTRIBUTOR_FN, &["&mut tuno::Song<T0>", "&mut tx_context::TxContext"]),
+    (PAY_ROYALTIES_FN, &["&mut tuno::Song<T0>", "address", "coin::Coin<T0>"]),
+    (GET_TOTAL_PRICE_FN, &["&tuno::Song<T0>", "address"]),
+    (UPDATE_SONG_METADATA_FN, &[
+        "&mut tuno::Song<T0>", "vector<u8>", "vector<u8>", "vector<u8>", "u64", "vector<u8>", "vector<u8>",
+        "u64", "&mut tx_context::TxContext"
+    ]),
+    (REFRESH_SONG_DISPLAY_FN, &[
+        "&tuno::Song<T0>", "&mut kiosk::Kiosk", "&kiosk::KioskOwnerCap", "&mut tx_context::TxContext"
+    ]),
+    (REMOVE_SONG_DISPLAY_FN, &[
+        "&tuno::Song<T0>", "&mut kiosk::Kiosk", "&kiosk::KioskOwnerCap", "object::ID"
+    ]),
+];
+
+/// Functions of the deployed package that this client cannot call, with the reason
+#[derive(Default)]
+pub(crate) struct Compatibility {
+    issues: BTreeMap<&'static str, String>,
+}
+
+impl Compatibility {
+    /// Fails if the deployed `function` is missing or takes other parameters
+    pub(crate) fn require(&self, function: &str) -> Result<(), Error> {
+        match self.issues.get(function) {
+            Some(issue) => Err(Error::Incompatible(issue.clone())),
+            None => Ok(())
+        }
+    }
+
+    pub(crate) fn issues(&self) -> impl Iterator<Item = &String> {
+        self.issues.values()
+    }
+}
+
+/// Compares the functions of the deployed package with the ones the client calls.
+/// Only a package without the `tuno` module is rejected outright, the commands calling
+/// a mismatched function fail when they call it
+pub(crate) async fn check_package(wallet: &WalletContext, package_id: ObjectID) -> Result<Compatibility, Error> {
+    let mut modules = wallet.get_client().await.map_err(Error::Rpc)?
+        .read_api()
+        .get_normalized_move_modules_by_package(package_id).await?;
+
+    let Some(module) = modules.remove(TUNO_MODULE) else {
+        return Err(Error::Incompatible(format!("package {package_id} has no `{TUNO_MODULE}` module")));
+    };
+
+    let issues: BTreeMap<_, _> = EXPECTED_FUNCTIONS.iter()
+        .filter_map(|(name, expected)| {
+            let issue = check_function(name, expected, module.exposed_functions.get(*name))?;
+            Some((*name, format!("package {package_id}: {issue}")))
+        })
+        .collect();
+
+    if issues.is_empty() {
+        trace!("Package {package_id} is compatible");
+    }
+
+    Ok(Compatibility { issues })
+}
+
+fn check_function(
+    name: &str,
+    expected: &[&str],
+    function: Option<&IotaMoveNormalizedFunction>
+) -> Option<String> {
+    let Some(function) = function else {
+        return Some(format!("`{name}` is missing"));
+    };
+
+    let found: Vec<_> = function.parameters.iter().map(describe).collect();
+    if found != expected {
+        return Some(format!(
+            "`{name}` takes ({}), expected ({})",
+            found.join(", "),
+            expected.join(", ")
+        ));
+    }
+
+    None
+}
+
+/// Renders a type without the address of its package, which changes between deployments
+fn describe(t: &IotaMoveNormalizedType) -> String {
+    match t {
+        IotaMoveNormalizedType::Bool => "bool".to_string(),
+        IotaMoveNormalizedType::U8 => "u8".to_string(),
+        IotaMoveNormalizedType::U16 => "u16".to_string(),
+        IotaMoveNormalizedType::U32 => "u32".to_string(),
+        IotaMoveNormalizedType::U64 => "u64".to_string(),
+        IotaMoveNormalizedType::U128 => "u128".to_string(),
+        IotaMoveNormalizedType::U256 => "u256".to_string(),
+        IotaMoveNormalizedType::Address => "address".to_string(),
+        IotaMoveNormalizedType::Signer => "signer".to_string(),
+        IotaMoveNormalizedType::Struct { module, name, type_arguments, .. } => {
+            if type_arguments.is_empty() {
+                format!("{module}::{name}")
+            } else {
+                let arguments: Vec<_> = type_arguments.iter().map(describe).collect();
+                format!("{module}::{name}<{}>", arguments.join(", "))
+            }
+        },
+        IotaMoveNormalizedType::Vector(inner) => format!("vector<{}>", describe(inner)),
+        IotaMoveNormalizedType::TypeParameter(index) => format!("T{index}"),
+        IotaMoveNormalizedType::Reference(inner) => format!("&{}", describe(inner)),
+        IotaMoveNormalizedType::MutableReference(inner) => format!("&mut {}", describe(inner)),
+    }
+}
@@ -1,4 +1,55 @@
 pub const DEFAULT_MEDIA_STORAGE: &str = "media";
+pub const DEFAULT_INDEX_STORAGE: &str = "index";
 pub const TUNO_BASE_CHUNK_SIZE: usize = 512 * 512;
 
 pub const USDC_TYPE_TAG_STR: &str = "0x493acfe10ce496bafec59019248bed5045cb79b65e8a05451f3f9f9cabede81f::usdc::USDC";
+
+/// Maximum number of objects the read API resolves in a single `multi_get` call
+pub const MULTI_GET_OBJECT_MAX_SIZE: usize = 50;
+
+/// Maximum number of coins merged into another within a single transaction
+pub const MAX_MERGE_COINS_PER_TX: usize = 500;
+
+/// Upper bound of the budget used to dry run transactions, in NANOS
+pub const MAX_GAS_BUDGET: u64 = 50_000_000_000;
+pub const DEFAULT_GAS_MULTIPLIER: f64 = 1.2;
+
+/// Maximum number of songs a single batched transaction calls into, well below the
+/// protocol's limits on commands and input objects
+pub const MAX_SONGS_PER_BATCH: usize = 256;
+
+/// Maximum size of the pure arguments of a batched transaction, below the protocol's transaction size limit
+pub const MAX_BATCH_ARGS_SIZE: usize = 96 * 1024;
+
+/// Largest embedded cover art (in bytes) that is inlined as a data URL when publishing
+pub const MAX_INLINE_COVER_ART_SIZE: usize = 8 * 1024;
+
+/// Module of the package holding every function the client calls
+pub const TUNO_MODULE: &str = "tuno";
+
+/// Functions of the `tuno` module called by the client, checked against the deployed package on connection
+pub const REGISTER_CREATOR_FN: &str = "register_creator";
+pub const CREATE_SONG_FN: &str = "create_song";
+pub const MAKE_SONG_AVAILABLE_FN: &str = "make_song_available";
+pub const MAKE_SONG_UNAVAILABLE_FN: &str = "make_song_unavailable";
+pub const REGISTER_AS_DISTRIBUTOR_FN: &str = "register_as_distributor";
+pub const REMOVE_AS_DISTRIBUTOR_FN: &str = "remove_as_distributor";
+pub const PAY_ROYALTIES_FN: &str = "pay_royalties";
+pub const GET_TOTAL_PRICE_FN: &str = "get_total_price";
+pub const UPDATE_SONG_METADATA_FN: &str = "update_song_metadata";
+pub const REFRESH_SONG_DISPLAY_FN: &str = "refresh_song_display";
+pub const REMOVE_SONG_DISPLAY_FN: &str = "remove_song_display";
+
+/// Module of the framework managing kiosks, and the functions the client calls
+pub const KIOSK_MODULE: &str = "kiosk";
+pub const KIOSK_WITHDRAW_FN: &str = "withdraw";
+pub const KIOSK_TAKE_FN: &str = "take";
+pub const KIOSK_SET_OWNER_CUSTOM_FN: &str = "set_owner_custom";
+
+/// Location of the saved profiles, one file per network under the user's config directory
+pub const PROFILES_DIR: &str = "tuno";
+pub const PROFILES_STORAGE: &str = "profiles";
+/// Network of the profiles when the wallet has no active environment
+pub const DEFAULT_NETWORK: &str = "default";
+/// Name of the profile saved at registration
+pub const DEFAULT_PROFILE: &str = "default";
@@ -1,7 +1,17 @@
-use iota_sdk::types::base_types::IotaAddress;
+use std::path::PathBuf;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use iota_sdk::types::digests::TransactionDigest;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
 use tabled::{Table, Tabled};
 use std::fmt::{Display, Formatter};
 
+use crate::client::{CreatorSetup, SongMetadata};
+use crate::local_storage::LocalCopy;
+use crate::output::CommandOutput;
+use crate::media::MediaInfo;
+use crate::profiles::{Profile, Profiles};
 use crate::types::*;
 
 #[derive(Tabled)]
@@ -58,7 +68,7 @@ impl Display for SongDisplayList {
     }
 }
 
-#[derive(Tabled)]
+#[derive(Tabled, Serialize)]
 struct TabledDistributor {
     address: String,
     url: String,
@@ -84,3 +94,516 @@ impl Display for DistributionMap {
         write!(f, "{}", Table::new(self.0.iter().map(|entry| TabledDistributor::from(entry))))
     }
 }
+
+#[derive(Tabled, Serialize)]
+pub struct TabledPublishedTrack {
+    pub file: String,
+    pub title: String,
+    pub mime_type: String,
+    pub song_id: String,
+    pub available: bool,
+    pub status: String,
+}
+
+#[derive(Serialize)]
+#[serde(transparent)]
+pub struct PublishReport(pub Vec<TabledPublishedTrack>);
+
+impl Display for PublishReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Table::new(&self.0))
+    }
+}
+
+#[derive(Tabled)]
+struct TabledMetadataField {
+    field: &'static str,
+    value: String,
+}
+
+impl Display for SongMetadata {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cover_art_url = match self.cover_art_url.split_once(";base64,") {
+            Some((media_type, data)) => format!("{media_type} (embedded, {} bytes)", data.len()),
+            None => self.cover_art_url.clone()
+        };
+
+        write!(f, "{}", Table::new([
+            TabledMetadataField { field: "title", value: self.title.clone() },
+            TabledMetadataField { field: "artist", value: self.artist.clone() },
+            TabledMetadataField { field: "album", value: self.album.clone() },
+            TabledMetadataField { field: "release year", value: self.release_year.to_string() },
+            TabledMetadataField { field: "genre", value: self.genre.clone() },
+            TabledMetadataField { field: "cover art", value: cover_art_url },
+            TabledMetadataField { field: "streaming price", value: self.streaming_price.to_string() },
+        ]))
+    }
+}
+
+impl Display for MediaInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let unknown = || "unknown".to_string();
+        let (minutes, millis) = (self.duration / 60_000, self.duration % 60_000);
+
+        write!(f, "{}", Table::new([
+            TabledMetadataField { field: "format", value: self.format.to_string() },
+            TabledMetadataField { field: "mime type", value: self.format.mime_type().to_string() },
+            TabledMetadataField { field: "codec", value: self.codec.clone() },
+            TabledMetadataField {
+                field: "bitrate",
+                value: self.bitrate().map_or_else(unknown, |b| format!("{b} kbit/s"))
+            },
+            TabledMetadataField {
+                field: "sample rate",
+                value: self.sample_rate.map_or_else(unknown, |r| format!("{r} Hz"))
+            },
+            TabledMetadataField {
+                field: "channels",
+                value: self.channels.map_or_else(unknown, |c| c.to_string())
+            },
+            TabledMetadataField {
+                field: "duration",
+                value: format!("{minutes}:{:02}.{:03}", millis / 1000, millis % 1000)
+            },
+            TabledMetadataField { field: "size", value: format!("{} bytes", self.size) },
+            TabledMetadataField { field: "chunks", value: self.chunks().to_string() },
+        ]))
+    }
+}
+
+/// Every detail of a song, as printed by `music get-song`
+#[derive(Serialize)]
+pub struct SongDetails {
+    id: String,
+    title: String,
+    artist: String,
+    album: String,
+    release_year: usize,
+    genre: String,
+    cover_art_url: String,
+    streaming_price: usize,
+    owner: String,
+    length: usize,
+    duration: String,
+    chunks: usize,
+    creator_balance: usize,
+    available: bool,
+    display_id: Option<String>,
+    distributors: Vec<TabledDistributor>,
+    local_copy: LocalCopy,
+}
+
+impl SongDetails {
+    pub(crate) fn new(song: &Song, local_copy: LocalCopy) -> Self {
+        let seconds = song.duration / 1000;
+
+        Self {
+            id: song.id.to_string(),
+            title: song.title.clone(),
+            artist: song.artist.clone(),
+            album: song.album.clone(),
+            release_year: song.release_year,
+            genre: song.genre.clone(),
+            cover_art_url: song.cover_art_url.clone(),
+            streaming_price: song.streaming_price,
+            owner: song.owner.to_string(),
+            length: song.length,
+            duration: format!("{}:{:02}", seconds / 60, seconds % 60),
+            chunks: song.signature.sig.len(),
+            creator_balance: song.creator_balance,
+            available: song.display_id.is_some(),
+            display_id: song.display_id.map(|id| id.to_string()),
+            distributors: song.distributors.0.iter().map(TabledDistributor::from).collect(),
+            local_copy
+        }
+    }
+}
+
+impl Display for SongDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let cover_art_url = match self.cover_art_url.split_once(";base64,") {
+            Some((media_type, data)) => format!("{media_type} (embedded, {} bytes)", data.len()),
+            None => self.cover_art_url.clone()
+        };
+
+        writeln!(f, "{}", Table::new([
+            TabledMetadataField { field: "id", value: self.id.clone() },
+            TabledMetadataField { field: "title", value: self.title.clone() },
+            TabledMetadataField { field: "artist", value: self.artist.clone() },
+            TabledMetadataField { field: "album", value: self.album.clone() },
+            TabledMetadataField { field: "release year", value: self.release_year.to_string() },
+            TabledMetadataField { field: "genre", value: self.genre.clone() },
+            TabledMetadataField { field: "cover art", value: cover_art_url },
+            TabledMetadataField { field: "streaming price", value: self.streaming_price.to_string() },
+            TabledMetadataField { field: "owner", value: self.owner.clone() },
+            TabledMetadataField { field: "length", value: format!("{} bytes", self.length) },
+            TabledMetadataField { field: "duration", value: self.duration.clone() },
+            TabledMetadataField { field: "chunks", value: self.chunks.to_string() },
+            TabledMetadataField { field: "creator balance", value: self.creator_balance.to_string() },
+            TabledMetadataField { field: "available", value: self.available.to_string() },
+            TabledMetadataField {
+                field: "display id",
+                value: self.display_id.clone().unwrap_or_else(|| "none".to_string())
+            },
+            TabledMetadataField { field: "local copy", value: self.local_copy.to_string() },
+        ]))?;
+
+        match self.distributors.is_empty() {
+            true => write!(f, "No distributors"),
+            false => write!(f, "{}", Table::new(&self.distributors))
+        }
+    }
+}
+
+impl CommandOutput for SongDetails {}
+
+impl CommandOutput for MediaInfo {}
+
+fn rows_of<T: Serialize>(items: impl IntoIterator<Item = T>) -> Result<Vec<Value>> {
+    Ok(items.into_iter().map(serde_json::to_value).collect::<Result<_, _>>()?)
+}
+
+impl CommandOutput for SongList {
+    fn rows(&self) -> Result<Vec<Value>> {
+        rows_of(&self.0)
+    }
+}
+
+impl CommandOutput for SongDisplayList {
+    fn rows(&self) -> Result<Vec<Value>> {
+        rows_of(&self.0)
+    }
+}
+
+impl CommandOutput for DistributionMap {
+    fn rows(&self) -> Result<Vec<Value>> {
+        rows_of(self.0.iter().map(TabledDistributor::from))
+    }
+}
+
+impl CommandOutput for PublishReport {
+    fn rows(&self) -> Result<Vec<Value>> {
+        rows_of(&self.0)
+    }
+}
+
+/// One page of a listing, with the objects that could not be read
+#[derive(Serialize)]
+pub struct Listing<T> {
+    pub items: T,
+    pub skipped: Vec<String>,
+    pub next_cursor: Option<ObjectID>,
+}
+
+impl<T: Display> Display for Listing<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.items)?;
+        for e in &self.skipped {
+            write!(f, "\nSkipped malformed object: {e}")?;
+        }
+        if let Some(cursor) = self.next_cursor {
+            write!(f, "\nNext cursor: {cursor}")?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Rows are the listed items only, skipped objects and cursor are left to the document formats
+impl<T: CommandOutput> CommandOutput for Listing<T> {
+    fn rows(&self) -> Result<Vec<Value>> {
+        self.items.rows()
+    }
+}
+
+/// Transaction executed on a song
+#[derive(Serialize)]
+pub struct SongTransaction {
+    pub song: ObjectID,
+    pub digest: TransactionDigest,
+    #[serde(skip)]
+    pub action: &'static str,
+}
+
+impl Display for SongTransaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Song ({}) {} [{}]", self.song, self.action, self.digest)
+    }
+}
+
+impl CommandOutput for SongTransaction {}
+
+#[derive(Serialize)]
+pub struct CreatorRegistration {
+    pub digest: TransactionDigest,
+    #[serde(flatten)]
+    pub creator: CreatorSetup,
+}
+
+impl Display for CreatorRegistration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Creator succesfully registered on {}", self.digest)?;
+        writeln!(f, "CreatorCap: {}", self.creator.cap)?;
+        writeln!(f, "Kiosk: {}", self.creator.kiosk)?;
+        write!(f, "KioskOwnerCap: {}", self.creator.kiosk_cap)
+    }
+}
+
+impl CommandOutput for CreatorRegistration {}
+
+#[derive(Serialize)]
+pub struct PublishedSong {
+    pub id: ObjectID,
+    pub digest: TransactionDigest,
+    /// Digest of the transaction that made the song available, if it was
+    pub available: Option<TransactionDigest>,
+    /// Why the song could not be made available
+    pub unavailable: Option<String>,
+    pub location: PathBuf,
+}
+
+impl Display for PublishedSong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Song succesfully published [{}]", self.digest)?;
+        writeln!(f, "ID: {}", self.id)?;
+        match (&self.available, &self.unavailable) {
+            (Some(digest), _) => writeln!(f, "Status: + [{digest}]")?,
+            (None, Some(e)) => writeln!(f, "Status: - [{e}]")?,
+            (None, None) => writeln!(f, "Status: -")?
+        }
+        write!(f, "location: {}", self.location.display())
+    }
+}
+
+impl CommandOutput for PublishedSong {}
+
+/// Song file whose signature was verified, then stored locally
+#[derive(Serialize)]
+pub struct StoredSong {
+    pub song: ObjectID,
+    pub location: PathBuf,
+}
+
+impl Display for StoredSong {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "File's signature verified")?;
+        write!(f, "location: {}", self.location.display())
+    }
+}
+
+impl CommandOutput for StoredSong {}
+
+#[derive(Serialize)]
+pub struct Consolidation {
+    pub coin: Option<ObjectID>,
+    pub digests: Vec<TransactionDigest>,
+}
+
+impl Display for Consolidation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Some(coin) = self.coin else {
+            return write!(f, "Nothing to consolidate");
+        };
+
+        write!(f, "USDC coins merged into {coin}")?;
+        for digest in &self.digests {
+            write!(f, "\n[{digest}]")?;
+        }
+
+        Ok(())
+    }
+}
+
+impl CommandOutput for Consolidation {}
+
+#[derive(Tabled)]
+struct TabledOrphanDisplay {
+    id: String,
+    song_id: String,
+    title: String,
+    artist: String,
+}
+
+impl From<&SongDisplay> for TabledOrphanDisplay {
+    fn from(display: &SongDisplay) -> Self {
+        Self {
+            id: display.id.to_string(),
+            song_id: display.song_id.to_string(),
+            title: display.title.clone(),
+            artist: display.artist.clone()
+        }
+    }
+}
+
+/// Kiosk's state, with the displays left behind by songs that no longer exist
+#[derive(Serialize)]
+pub struct KioskReport {
+    #[serde(flatten)]
+    pub info: KioskInfo,
+    pub orphans: SongDisplayList,
+}
+
+impl Display for KioskReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{}", Table::new([
+            TabledMetadataField { field: "id", value: self.info.id.to_string() },
+            TabledMetadataField { field: "owner", value: self.info.owner.to_string() },
+            TabledMetadataField { field: "items", value: self.info.item_count.to_string() },
+            TabledMetadataField { field: "profits", value: format!("{} NANOS", self.info.profits) },
+        ]))?;
+
+        match self.orphans.0.is_empty() {
+            true => write!(f, "No orphan display"),
+            false => write!(
+                f,
+                "Orphan displays, remove them with `kiosk remove-item --item <id>`:\n{}",
+                Table::new(self.orphans.0.iter().map(TabledOrphanDisplay::from))
+            )
+        }
+    }
+}
+
+impl CommandOutput for KioskReport {}
+
+/// Transaction executed on a kiosk
+#[derive(Serialize)]
+pub struct KioskTransaction {
+    pub kiosk: ObjectID,
+    pub digest: TransactionDigest,
+    #[serde(skip)]
+    pub action: String,
+}
+
+impl Display for KioskTransaction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Kiosk ({}) {} [{}]", self.kiosk, self.action, self.digest)
+    }
+}
+
+impl CommandOutput for KioskTransaction {}
+
+#[derive(Tabled)]
+struct TabledKioskCap {
+    kiosk: String,
+    kiosk_cap: String,
+}
+
+/// Capabilities owned by the active address
+#[derive(Serialize)]
+pub struct OwnedCapabilities {
+    pub creator_caps: Vec<CreatorCap>,
+    pub kiosk_caps: Vec<KioskCap>,
+}
+
+impl Display for OwnedCapabilities {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.creator_caps.is_empty() {
+            true => writeln!(f, "No creator capability, register with `music register`")?,
+            false => for cap in &self.creator_caps {
+                writeln!(f, "CreatorCap: {}", cap.id)?;
+            }
+        }
+
+        match self.kiosk_caps.is_empty() {
+            true => write!(f, "No kiosk owned"),
+            false => write!(f, "{}", Table::new(self.kiosk_caps.iter().map(|c| TabledKioskCap {
+                kiosk: c.kiosk.to_string(),
+                kiosk_cap: c.id.to_string()
+            })))
+        }
+    }
+}
+
+impl CommandOutput for OwnedCapabilities {}
+
+#[derive(Tabled)]
+struct TabledProfile {
+    active: &'static str,
+    name: String,
+    address: String,
+    kiosk: String,
+}
+
+/// Profiles saved for a network
+#[derive(Serialize)]
+pub struct ProfileList {
+    pub network: String,
+    pub active: Option<String>,
+    pub profiles: Vec<ProfileDetails>,
+}
+
+impl ProfileList {
+    pub(crate) fn new(profiles: &Profiles) -> Self {
+        let active = profiles.active().map(|(name, _)| name.to_string());
+        Self {
+            network: profiles.network().to_string(),
+            profiles: profiles.iter()
+                .map(|(name, profile)| ProfileDetails {
+                    name: name.clone(),
+                    network: profiles.network().to_string(),
+                    active: active.as_ref() == Some(name),
+                    profile: profile.clone()
+                })
+                .collect(),
+            active
+        }
+    }
+}
+
+impl Display for ProfileList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.profiles.is_empty() {
+            return write!(f, "No profile on {}, one is saved by `music register`", self.network);
+        }
+
+        write!(f, "Profiles on {}:\n{}", self.network, Table::new(self.profiles.iter().map(|p| TabledProfile {
+            active: if p.active { "*" } else { "" },
+            name: p.name.clone(),
+            address: p.profile.address.to_string(),
+            kiosk: p.profile.setup.kiosk.to_string()
+        })))
+    }
+}
+
+impl CommandOutput for ProfileList {
+    fn rows(&self) -> Result<Vec<Value>> {
+        rows_of(&self.profiles)
+    }
+}
+
+/// Saved profile, with the network it belongs to
+#[derive(Serialize)]
+pub struct ProfileDetails {
+    pub name: String,
+    pub network: String,
+    pub active: bool,
+    #[serde(flatten)]
+    pub profile: Profile,
+}
+
+impl ProfileDetails {
+    pub(crate) fn new(name: &str, profiles: &Profiles) -> Result<Self> {
+        Ok(Self {
+            name: name.to_string(),
+            network: profiles.network().to_string(),
+            active: profiles.active().is_some_and(|(active, _)| active == name),
+            profile: profiles.get(name)?.clone()
+        })
+    }
+}
+
+impl Display for ProfileDetails {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Table::new([
+            TabledMetadataField { field: "name", value: self.name.clone() },
+            TabledMetadataField { field: "network", value: self.network.clone() },
+            TabledMetadataField { field: "active", value: self.active.to_string() },
+            TabledMetadataField { field: "address", value: self.profile.address.to_string() },
+            TabledMetadataField { field: "creator cap", value: self.profile.setup.cap.to_string() },
+            TabledMetadataField { field: "kiosk", value: self.profile.setup.kiosk.to_string() },
+            TabledMetadataField { field: "kiosk cap", value: self.profile.setup.kiosk_cap.to_string() },
+        ]))
+    }
+}
+
+impl CommandOutput for ProfileDetails {}
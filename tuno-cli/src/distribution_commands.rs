@@ -1,15 +1,18 @@
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Result;
+use clap::builder::RangedU64ValueParser;
 use clap::Parser;
 use iota_sdk::types::base_types::ObjectID;
 use tokio::{signal, sync::oneshot};
-use tokio_stream::StreamExt;
 
 use crate::server::TunoGrpcServer;
 use crate::client::{Client, Connection};
 use crate::local_storage::{store_song_from_bytes, store_song_from_file};
-use crate::constants::TUNO_BASE_CHUNK_SIZE;
+use crate::displays::{SongTransaction, StoredSong};
+use crate::output::{print_output, OutputFormat};
+use crate::errors::Error;
 use crate::types::TunoSignature;
 
 pub mod pb {
@@ -29,6 +32,9 @@ pub enum DistributionCommands {
         /// Port to start RPC server on. (default: 4114)
         #[arg(long, default_value = "4114")]
         rpc_port: u16,
+        /// Number of gas coins used to execute transactions concurrently
+        #[arg(long, default_value = "8", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+        gas_pool_size: usize,
         
         #[command(flatten)]
         conn: Connection
@@ -56,7 +62,7 @@ pub enum DistributionCommands {
 
     /// Add song manually
     Add {
-        /// MP3 file containing the song
+        /// Audio file containing the song (MP3, FLAC, Ogg Vorbis/Opus, AAC/M4A or WAV)
         #[arg(long)]
         file: PathBuf,
         
@@ -80,22 +86,26 @@ pub enum DistributionCommands {
 }
 
 impl DistributionCommands {
-    pub async fn execute(self) -> Result<()> {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
         match self {
             DistributionCommands::Start {
                 cert_dir,
                 rpc_ip,
                 rpc_port,
+                gas_pool_size,
                 conn
             } => {
+                let mut client = Client::connect(conn).await?;
+                client.init_gas_pool(gas_pool_size).await?;
+                let client = Arc::new(client);
+
                 let server = TunoGrpcServer::new(
                     rpc_ip,
                     rpc_port,
                     cert_dir,
-                    conn.clone()
+                    client.clone()
                 );
 
-                let client = Client::new(conn)?;
                 let distributing = client.distribute_all(
                     &server.get_url(),
                     100_000
@@ -127,22 +137,20 @@ impl DistributionCommands {
                 song,
                 conn
             } => {
-                let client = Client::new(conn)?;
+                let client = Client::connect(conn).await?;
                 let digest = client.undistribute(song).await?;
 
-                println!("Song ({}) is not longer being distributed [{}]", song, digest);
-                Ok(())
+                print_output(&SongTransaction { song, digest, action: "is not longer being distributed" }, output)
             }
 
             DistributionCommands::List {
                 song,
                 conn
             } => {
-                let client = Client::new(conn)?;
+                let client = Client::connect(conn).await?;
                 let distributors = client.get_song(song).await?.distributors;
 
-                println!("{}", distributors);
-                Ok(())
+                print_output(&distributors, output)
             }
 
             DistributionCommands::Add {
@@ -150,57 +158,29 @@ impl DistributionCommands {
                 song,
                 conn
             } => {
-
-                let client = Client::new(conn)?;
+                let signature = TunoSignature::try_from(&file)?;
+                let client = Client::connect(conn).await?;
                 let obj = client.get_song(song).await?;
 
-                if TunoSignature::from(&file) != obj.signature {
-                    panic!("File's signature cannot be verified succesfully");
+                if signature != obj.signature {
+                    return Err(Error::Verification(format!("file's signature does not match song {song}")).into());
                 }
 
-                println!("File's signature verified");
-                println!("location: {}", store_song_from_file(&file, &song.to_hex())?.display());
+                let location = store_song_from_file(&file, &song.to_hex())?;
 
-                Ok(())
+                print_output(&StoredSong { song, location }, output)
             }
 
             DistributionCommands::Download {
                 song,
                 conn
             } => {
-                let client = Client::new(conn)?;
-                let mut obj = client.get_song(song).await?;
-
-                let (
-                    address,
-                    distributor
-                ) = obj.distributors.0.first_key_value().unwrap();
+                let client = Client::connect(conn).await?;
+                let data = client.download_song(song, |_, _| {}).await?;
 
-                let tx = client.get_payment_transaction(song, address).await?;
-                let mut channel = pb::tuno_client::TunoClient::connect(
-                    distributor.url.clone()
-                ).await?;
-
-                let mut stream = channel.stream_song(
-                    pb::SongStreamRequest {
-                        raw_transaction: hex::encode(bcs::to_bytes(&tx)?),
-                        block_size: 4 * TUNO_BASE_CHUNK_SIZE as u32,
-                    }
-                ).await?
-                .into_inner();
-
-                let mut data: Vec<u8> = vec![];
-                while let Some(item) = stream.next().await {
-                    match obj.signature.consume_data(item.unwrap().data) {
-                        Some(mut d) => data.append(&mut d),
-                        None => panic!("Received data cannot be verified succesfully")
-                    };
-                }
-            
-                println!("File's signature verified");
-                println!("location: {}", store_song_from_bytes(data, &song.to_hex())?.display());
+                let location = store_song_from_bytes(data, &song.to_hex())?;
 
-                Ok(())
+                print_output(&StoredSong { song, location }, output)
             }
         }
     }
@@ -0,0 +1,255 @@
+use thiserror::Error;
+
+use iota_sdk::types::base_types::ObjectID;
+use iota_sdk::types::digests::TransactionDigest;
+
+use crate::constants::TUNO_MODULE;
+use crate::media::MediaError;
+use crate::objects::ParseError;
+
+/// Failures of the library, by kind
+#[derive(Debug, Error)]
+pub enum Error {
+    #[error("Wallet configuration error: {0}")]
+    Config(String),
+    #[error("Wallet error: {0:#}")]
+    Wallet(anyhow::Error),
+    #[error("RPC error: {0:#}")]
+    Rpc(anyhow::Error),
+    #[error("Incompatible package: {0}")]
+    Incompatible(String),
+    #[error("Transaction {} aborted: {error}", digest.map_or("dry run".to_string(), |d| d.to_string()))]
+    Abort { error: TunoError, digest: Option<TransactionDigest> },
+    #[error("Transaction {digest} failed: {status}")]
+    Execution { digest: TransactionDigest, status: String },
+    #[error(transparent)]
+    Parse(#[from] ParseError),
+    #[error(transparent)]
+    Media(#[from] MediaError),
+    #[error("Local storage error: {0:#}")]
+    Storage(anyhow::Error),
+    #[error("Verification failed: {0}")]
+    Verification(String),
+    #[error(transparent)]
+    Other(anyhow::Error),
+}
+
+impl Error {
+    /// What the user can do about it, when known
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Config(_) => Some("pass the wallet's client.yaml with --config, or create one with `iota client`"),
+            Self::Incompatible(_) => Some("check --package-id, or update tuno-cli to match the deployed package"),
+            Self::Abort { error, .. } => Some(error.hint()),
+            _ => None
+        }
+    }
+
+    /// Exit code of the CLI for this kind of failure, stable across releases.
+    /// 2 is left to usage errors, reported by clap
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Other(_) => 1,
+            Self::Config(_) => 3,
+            Self::Wallet(_) => 4,
+            Self::Rpc(_) => 5,
+            Self::Incompatible(_) => 6,
+            Self::Abort { .. } => 7,
+            Self::Execution { .. } => 8,
+            Self::Parse(_) => 9,
+            Self::Media(_) => 10,
+            Self::Storage(_) => 11,
+            Self::Verification(_) => 12,
+        }
+    }
+}
+
+impl From<iota_sdk::error::Error> for Error {
+    fn from(e: iota_sdk::error::Error) -> Self {
+        Self::Rpc(e.into())
+    }
+}
+
+/// Sorts the failures of the crate's internals, typed at their source, into kinds
+impl From<anyhow::Error> for Error {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<Error>() {
+            Ok(e) => return e,
+            Err(e) => e
+        };
+
+        let e = match e.downcast::<TunoError>() {
+            Ok(error) => return Self::Abort { error, digest: None },
+            Err(e) => e
+        };
+
+        let e = match e.downcast::<ParseError>() {
+            Ok(error) => return Self::Parse(error),
+            Err(e) => e
+        };
+
+        let e = match e.downcast::<MediaError>() {
+            Ok(error) => return Self::Media(error),
+            Err(e) => e
+        };
+
+        if e.is::<iota_sdk::error::Error>() {
+            Self::Rpc(e)
+        } else if e.is::<rusqlite::Error>() || e.is::<std::io::Error>() {
+            Self::Storage(e)
+        } else {
+            Self::Other(e)
+        }
+    }
+}
+
+/// Location and code of a Move abort, read from an execution failure such as
+/// `MoveAbort(MoveLocation { module: ModuleId { address: .., name: Identifier("tuno") }, .. }, 4) in command 0`
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct MoveAbort {
+    pub package: ObjectID,
+    pub module: String,
+    pub code: u64,
+}
+
+impl MoveAbort {
+    pub(crate) fn parse(error: &str) -> Option<Self> {
+        let abort = &error[error.find("MoveAbort(")? + "MoveAbort(".len()..];
+
+        // The code follows the location, which holds nested braces
+        let mut depth = 0;
+        let end = abort.char_indices().find_map(|(i, c)| {
+            match c {
+                '{' => depth += 1,
+                '}' => {
+                    depth -= 1;
+                    if depth == 0 { return Some(i) }
+                },
+                _ => {}
+            }
+            None
+        })?;
+        let (location, rest) = abort.split_at(end + 1);
+
+        let module_id = location.split_once("ModuleId {")?.1.split_once('}')?.0;
+        let address = module_id.split_once("address: ")?.1.split_once(',')?.0.trim();
+        let module = module_id.split_once("name: Identifier(\"")?.1.split_once('"')?.0;
+        let code = rest.trim_start_matches([',', ' ']).split(')').next()?;
+
+        // Addresses are printed in full, without the `0x` prefix
+        let address = hex::decode(format!("{:0>64}", address.trim_start_matches("0x"))).ok()?;
+
+        Some(Self {
+            package: ObjectID::from_bytes(address).ok()?,
+            module: module.to_string(),
+            code: code.trim().parse().ok()?
+        })
+    }
+}
+
+/// Abort codes of the `tuno` module
+#[derive(Debug, Error, Clone, Copy, PartialEq, Eq)]
+pub enum TunoError {
+    #[error("the sender is not a distributor of this song (ENotDistributor)")]
+    NotDistributor,
+    #[error("the sender is not the owner of this song (ENotOwner)")]
+    NotOwner,
+    #[error("the amount does not match the expected one (EInsufficientAmount)")]
+    InsufficientAmount,
+    #[error("the song is not available (ENotAvailable)")]
+    NotAvailable,
+    #[error("the song is already available (EAlreadyAvailable)")]
+    AlreadyAvailable,
+    #[error("the song is not in this kiosk (ESongNotInKiosk)")]
+    SongNotInKiosk,
+    #[error("the song is listed with this display (EDisplayInUse)")]
+    DisplayInUse,
+}
+
+impl TunoError {
+    pub fn from_code(code: u64) -> Option<Self> {
+        match code {
+            1 => Some(Self::NotDistributor),
+            2 => Some(Self::NotOwner),
+            3 => Some(Self::InsufficientAmount),
+            4 => Some(Self::NotAvailable),
+            5 => Some(Self::AlreadyAvailable),
+            6 => Some(Self::SongNotInKiosk),
+            7 => Some(Self::DisplayInUse),
+            _ => None
+        }
+    }
+
+    /// Decodes an execution failure aborted by the `tuno` module of `package`
+    pub fn from_execution_error(error: &str, package: ObjectID) -> Option<Self> {
+        let abort = MoveAbort::parse(error)?;
+        if abort.package != package || abort.module != TUNO_MODULE {
+            return None;
+        }
+
+        Self::from_code(abort.code)
+    }
+
+    /// What the user can do about it
+    pub fn hint(&self) -> &'static str {
+        match self {
+            Self::NotDistributor => "register as a distributor of the song first (`tuno-cli distribution start`)",
+            Self::NotOwner => "only the creator of the song can do this, check the active address of the wallet",
+            Self::InsufficientAmount => "payments must match the song's price plus the distributor's fee, \
+                                         and withdrawals require a non-empty balance",
+            Self::NotAvailable => "the creator has to make the song available first (`tuno-cli music make-available`)",
+            Self::AlreadyAvailable => "nothing to do, the song is already listed in a kiosk",
+            Self::SongNotInKiosk => "use the kiosk in which the song was made available",
+            Self::DisplayInUse => "make the song unavailable instead (`tuno-cli music make-unavailable`)",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PACKAGE: &str = "5f2a0b3c5e8d4b1f9a7c6e3d2b1a09f8e7d6c5b4a3928170f6e5d4c3b2a19081";
+
+    fn abort(address: &str, module: &str, code: u64) -> String {
+        format!(
+            "MoveAbort(MoveLocation {{ module: ModuleId {{ address: {address}, name: Identifier(\"{module}\") }}, \
+             function: 4, instruction: 12, function_name: Some(\"pay_royalties\") }}, {code}) in command 0"
+        )
+    }
+
+    fn package() -> ObjectID {
+        ObjectID::from_hex_literal(&format!("0x{PACKAGE}")).unwrap()
+    }
+
+    #[test]
+    fn parses_abort_location() {
+        assert_eq!(
+            MoveAbort::parse(&abort(PACKAGE, "tuno", 4)),
+            Some(MoveAbort { package: package(), module: "tuno".to_string(), code: 4 })
+        );
+        assert_eq!(MoveAbort::parse(&abort("0x2", "kiosk", 1)).map(|a| a.package), Some(ObjectID::from_single_byte(2)));
+        assert_eq!(MoveAbort::parse("InsufficientGas in command 0"), None);
+    }
+
+    #[test]
+    fn decodes_aborts_of_the_package() {
+        assert_eq!(
+            TunoError::from_execution_error(&abort(PACKAGE, "tuno", 4), package()),
+            Some(TunoError::NotAvailable)
+        );
+        assert_eq!(
+            TunoError::from_execution_error(&abort(PACKAGE, "tuno", 6), package()),
+            Some(TunoError::SongNotInKiosk)
+        );
+    }
+
+    #[test]
+    fn ignores_aborts_of_other_modules_and_packages() {
+        let other = "00000000000000000000000000000000000000000000000000000000000000ab";
+
+        assert_eq!(TunoError::from_execution_error(&abort(other, "tuno", 4), package()), None);
+        assert_eq!(TunoError::from_execution_error(&abort(PACKAGE, "kiosk", 4), package()), None);
+        assert_eq!(TunoError::from_execution_error(&abort(PACKAGE, "tuno", 42), package()), None);
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use anyhow::{anyhow, bail, Result};
+use tokio::sync::Semaphore;
+
+use iota_sdk::types::base_types::ObjectID;
+
+/// Gas coins of the active address, each leased to a single transaction at a time
+/// so that concurrent transactions never lock the same gas object
+pub(crate) struct GasPool {
+    coins: Mutex<Vec<ObjectID>>,
+    all: Vec<ObjectID>,
+    available: Semaphore,
+    size: usize,
+}
+
+pub(crate) struct GasLease<'a> {
+    pool: &'a GasPool,
+    coin: ObjectID,
+}
+
+impl GasPool {
+    /// Fails without coins, as nothing could ever be leased
+    pub(crate) fn new(coins: Vec<ObjectID>) -> Result<Self> {
+        if coins.is_empty() {
+            bail!("Gas pool needs at least one gas coin");
+        }
+
+        Ok(Self {
+            available: Semaphore::new(coins.len()),
+            size: coins.len(),
+            all: coins.clone(),
+            coins: Mutex::new(coins),
+        })
+    }
+
+    pub(crate) fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Waits for a gas coin to be free, the coin is given back once the lease is dropped
+    pub(crate) async fn lease(&self) -> Result<GasLease<'_>> {
+        self.available.acquire().await?.forget();
+
+        let coin = self.coins()
+            .pop()
+            .ok_or_else(|| anyhow!("Gas pool is empty"))?;
+
+        Ok(GasLease { pool: self, coin })
+    }
+
+    /// Whether `coin` is one of the pool's gas coins, leased or not
+    pub(crate) fn contains(&self, coin: &ObjectID) -> bool {
+        self.all.contains(coin)
+    }
+
+    /// Free coins, still consistent after a panic as they are only pushed and popped
+    fn coins(&self) -> MutexGuard<'_, Vec<ObjectID>> {
+        self.coins.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl GasLease<'_> {
+    pub(crate) fn coin(&self) -> ObjectID {
+        self.coin
+    }
+}
+
+impl Drop for GasLease<'_> {
+    fn drop(&mut self) {
+        self.pool.coins().push(self.coin);
+        self.pool.available.add_permits(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use futures::future::join_all;
+    use iota_sdk::types::base_types::ObjectID;
+
+    use super::GasPool;
+
+    #[tokio::test]
+    async fn concurrent_leases_never_share_a_coin() {
+        let coins: Vec<_> = (0..3).map(|_| ObjectID::random()).collect();
+        let pool = GasPool::new(coins.clone()).unwrap();
+        let leased = Mutex::new(HashSet::new());
+        let most_leased = Mutex::new(0);
+
+        join_all((0..20).map(|_| async {
+            let lease = pool.lease().await.unwrap();
+            {
+                let mut leased = leased.lock().unwrap();
+                assert!(leased.insert(lease.coin()), "coin {} leased twice", lease.coin());
+                let mut most_leased = most_leased.lock().unwrap();
+                *most_leased = (*most_leased).max(leased.len());
+            }
+
+            tokio::time::sleep(Duration::from_millis(5)).await;
+            leased.lock().unwrap().remove(&lease.coin());
+        })).await;
+
+        assert_eq!(*most_leased.lock().unwrap(), coins.len());
+        assert_eq!(pool.coins().len(), coins.len());
+    }
+
+    #[test]
+    fn empty_pool_is_rejected() {
+        assert!(GasPool::new(vec![]).is_err());
+    }
+
+    #[tokio::test]
+    async fn lease_is_given_back_when_the_lock_is_poisoned() {
+        let pool = GasPool::new(vec![ObjectID::random(), ObjectID::random()]).unwrap();
+        let lease = pool.lease().await.unwrap();
+
+        std::thread::scope(|s| {
+            let _ = s.spawn(|| {
+                let _coins = pool.coins.lock().unwrap();
+                panic!("poisoning the gas pool");
+            }).join();
+        });
+        assert!(pool.coins.is_poisoned());
+
+        drop(lease);
+        let first = pool.lease().await.unwrap();
+        let second = pool.lease().await.unwrap();
+        assert_ne!(first.coin(), second.coin());
+    }
+}
@@ -0,0 +1,106 @@
+use anyhow::{bail, Result};
+use serde_json::Value;
+
+use iota_sdk::rpc_types::IotaEvent;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+
+/// Events emitted by the `tuno` module that are tracked by the indexer
+#[derive(Debug)]
+pub(crate) enum TunoEvent {
+    SongCreated {
+        id: ObjectID,
+        owner: IotaAddress,
+        title: String,
+        artist: String,
+        streaming_price: u64
+    },
+    SongBecameAvailable {
+        id: ObjectID,
+        kiosk_id: ObjectID,
+        streaming_price: u64
+    },
+    SongBecameUnavailable {
+        id: ObjectID,
+        kiosk_id: ObjectID
+    },
+    SongUpdated {
+        id: ObjectID,
+        title: String,
+        artist: String,
+        streaming_price: u64
+    },
+    DistributorAdded {
+        song_id: ObjectID,
+        distributor: IotaAddress,
+        url: String,
+        streaming_price: u64
+    },
+    DistributorRemoved {
+        song_id: ObjectID,
+        distributor: IotaAddress
+    },
+}
+
+impl TunoEvent {
+    /// Parses an event emitted by the package, `None` for events not tracked by the indexer
+    pub(crate) fn parse(event: &IotaEvent) -> Result<Option<Self>> {
+        let json = &event.parsed_json;
+        let parsed = match event.type_.name.as_str() {
+            "SongCreated" => Self::SongCreated {
+                id: parse_id(json, "id")?,
+                owner: event.sender,
+                title: parse_string(json, "title")?,
+                artist: parse_string(json, "artist")?,
+                streaming_price: parse_u64(json, "streaming_price")?
+            },
+            "SongBecameAvailable" => Self::SongBecameAvailable {
+                id: parse_id(json, "id")?,
+                kiosk_id: parse_id(json, "kiosk_id")?,
+                streaming_price: parse_u64(json, "streaming_price")?
+            },
+            "SongBecameUnavailable" => Self::SongBecameUnavailable {
+                id: parse_id(json, "id")?,
+                kiosk_id: parse_id(json, "kiosk_id")?
+            },
+            "SongUpdated" => Self::SongUpdated {
+                id: parse_id(json, "id")?,
+                title: parse_string(json, "title")?,
+                artist: parse_string(json, "artist")?,
+                streaming_price: parse_u64(json, "streaming_price")?
+            },
+            "DistributorAdded" => Self::DistributorAdded {
+                song_id: parse_id(json, "song_id")?,
+                distributor: parse_address(json, "distributor")?,
+                url: parse_string(json, "url")?,
+                streaming_price: parse_u64(json, "streaming_price")?
+            },
+            "DistributorRemoved" => Self::DistributorRemoved {
+                song_id: parse_id(json, "song_id")?,
+                distributor: parse_address(json, "distributor")?
+            },
+            _ => return Ok(None)
+        };
+
+        Ok(Some(parsed))
+    }
+}
+
+fn parse_string(json: &Value, field_name: &str) -> Result<String> {
+    match json[field_name].as_str() {
+        Some(parsed) => Ok(parsed.to_string()),
+        None => bail!("Error parsing {field_name} from {json}")
+    }
+}
+
+fn parse_id(json: &Value, field_name: &str) -> Result<ObjectID> {
+    Ok(ObjectID::from_hex_literal(&parse_string(json, field_name)?)?)
+}
+
+fn parse_address(json: &Value, field_name: &str) -> Result<IotaAddress> {
+    Ok(IotaAddress::from(parse_id(json, field_name)?))
+}
+
+// u64 values are rendered as strings in the JSON representation of Move values
+fn parse_u64(json: &Value, field_name: &str) -> Result<u64> {
+    Ok(parse_string(json, field_name)?.parse()?)
+}
@@ -0,0 +1,145 @@
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use anyhow::{anyhow, Result};
+use log::{info, trace, warn};
+use rusqlite::Connection;
+use tokio::{signal, time};
+
+use iota_sdk::rpc_types::EventFilter;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use iota_sdk::types::Identifier;
+use iota_sdk::wallet_context::WalletContext;
+
+mod events;
+mod store;
+
+pub use store::{AvailabilityChange, IndexedDistributor, IndexedSong};
+
+use crate::client::Pagination;
+use crate::constants::{DEFAULT_INDEX_STORAGE, TUNO_MODULE};
+use events::TunoEvent;
+
+/// Local index of the events emitted by a Tuno package, persisted in SQLite
+pub struct Indexer {
+    conn: Mutex<Connection>,
+    package_id: ObjectID,
+}
+
+impl Indexer {
+    /// Opens (or creates) the index of `package_id` in the default index storage
+    pub fn open(package_id: ObjectID) -> Result<Self> {
+        let location = PathBuf::from(DEFAULT_INDEX_STORAGE);
+        std::fs::create_dir_all(&location)?;
+
+        Ok(Self {
+            conn: Mutex::new(store::open(&location.join(format!("{}.sqlite", package_id.to_hex())))?),
+            package_id,
+        })
+    }
+
+    /// Fetches every event emitted since the persisted cursor, returns the number of indexed events
+    pub async fn sync(&self, wallet: &WalletContext) -> Result<usize> {
+        let client = wallet.get_client().await?;
+        let filter = EventFilter::MoveEventModule {
+            package: self.package_id,
+            module: Identifier::new(TUNO_MODULE)?
+        };
+
+        let mut cursor = store::get_cursor(&*self.lock()?)?;
+        let mut indexed = 0;
+        loop {
+            let page = client.event_api()
+                .query_events(filter.clone(), cursor, None, false).await?;
+
+            {
+                let mut conn = self.lock()?;
+                let tx = conn.transaction()?;
+                for event in &page.data {
+                    match TunoEvent::parse(event) {
+                        Ok(Some(parsed)) => {
+                            trace!("Indexing {:?}", parsed);
+                            store::apply_event(&tx, &parsed, &event.id.tx_digest, event.timestamp_ms)?;
+                            indexed += 1;
+                        },
+                        Ok(None) => continue,
+                        Err(e) => warn!("Skipping event {:?}: {e}", event.id)
+                    }
+                }
+
+                if let Some(next) = &page.next_cursor {
+                    store::set_cursor(&tx, next)?;
+                    cursor = Some(*next);
+                }
+                tx.commit()?;
+            }
+
+            if !page.has_next_page {
+                break;
+            }
+        }
+
+        Ok(indexed)
+    }
+
+    /// Keeps the index up to date until a ctrl+c signal is received
+    pub async fn run(&self, wallet: &WalletContext, interval: Duration) -> Result<()> {
+        let mut ticker = time::interval(interval);
+        loop {
+            tokio::select! {
+                _ = ticker.tick() => match self.sync(wallet).await {
+                    Ok(0) => continue,
+                    Ok(n) => info!("Indexed {n} event(s)"),
+                    Err(e) => warn!("Error while indexing: {e}")
+                },
+                _ = signal::ctrl_c() => break
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Songs created by `owner` in creation order, with the cursor to resume after them if any remain
+    pub fn songs_by_owner(
+        &self,
+        owner: &IotaAddress,
+        pagination: &Pagination
+    ) -> Result<(Vec<IndexedSong>, Option<ObjectID>)> {
+        let mut songs = store::query_songs_by_owner(
+            &*self.lock()?,
+            owner,
+            pagination.cursor.as_ref(),
+            pagination.limit.map(|l| l.get() + 1)
+        )?;
+
+        if pagination.limit.is_some_and(|l| songs.len() > l.get()) {
+            songs.pop();
+            let cursor = songs.last().map(|s| s.id);
+            return Ok((songs, cursor));
+        }
+
+        Ok((songs, None))
+    }
+
+    /// Kiosk in which the song was last made available, none if it is not available
+    pub fn kiosk_of(&self, song: &ObjectID) -> Result<Option<ObjectID>> {
+        store::query_song_kiosk(&*self.lock()?, song)
+    }
+
+    pub fn songs_by_distributor(&self, distributor: &IotaAddress) -> Result<Vec<IndexedDistributor>> {
+        store::query_distributors(&*self.lock()?, "distributor", &distributor.to_string())
+    }
+
+    pub fn distributors_of(&self, song: &ObjectID) -> Result<Vec<IndexedDistributor>> {
+        store::query_distributors(&*self.lock()?, "song_id", &song.to_string())
+    }
+
+    pub fn availability_changes(&self, song: &ObjectID) -> Result<Vec<AvailabilityChange>> {
+        store::query_availability_changes(&*self.lock()?, song)
+    }
+
+    fn lock(&self) -> Result<std::sync::MutexGuard<'_, Connection>> {
+        self.conn.lock().map_err(|_| anyhow!("Index database lock is poisoned"))
+    }
+}
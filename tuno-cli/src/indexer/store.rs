@@ -0,0 +1,335 @@
+use std::path::Path;
+use std::str::FromStr as _;
+
+use anyhow::Result;
+use rusqlite::{params, Connection, OptionalExtension as _, Transaction};
+
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use iota_sdk::types::digests::TransactionDigest;
+use iota_sdk::types::event::EventID;
+
+use crate::indexer::events::TunoEvent;
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS cursor (
+        id INTEGER PRIMARY KEY CHECK (id = 0),
+        tx_digest TEXT NOT NULL,
+        event_seq INTEGER NOT NULL
+    );
+
+    CREATE TABLE IF NOT EXISTS songs (
+        id TEXT PRIMARY KEY,
+        owner TEXT NOT NULL,
+        title TEXT NOT NULL,
+        artist TEXT NOT NULL,
+        streaming_price INTEGER NOT NULL,
+        kiosk_id TEXT,
+        created_at INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS songs_owner ON songs (owner);
+
+    CREATE TABLE IF NOT EXISTS availability (
+        song_id TEXT NOT NULL,
+        kiosk_id TEXT NOT NULL,
+        available INTEGER NOT NULL,
+        streaming_price INTEGER,
+        tx_digest TEXT NOT NULL,
+        timestamp_ms INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS availability_song ON availability (song_id);
+
+    CREATE TABLE IF NOT EXISTS distributors (
+        song_id TEXT NOT NULL,
+        distributor TEXT NOT NULL,
+        url TEXT NOT NULL,
+        streaming_price INTEGER NOT NULL,
+        PRIMARY KEY (song_id, distributor)
+    );
+    CREATE INDEX IF NOT EXISTS distributors_distributor ON distributors (distributor);
+";
+
+/// Song as known from the events emitted at creation and on availability changes
+#[derive(Debug)]
+pub struct IndexedSong {
+    pub id: ObjectID,
+    pub owner: IotaAddress,
+    pub title: String,
+    pub artist: String,
+    pub streaming_price: u64,
+    pub kiosk_id: Option<ObjectID>,
+}
+
+#[derive(Debug)]
+pub struct IndexedDistributor {
+    pub song_id: ObjectID,
+    pub distributor: IotaAddress,
+    pub url: String,
+    pub streaming_price: u64,
+}
+
+#[derive(Debug)]
+pub struct AvailabilityChange {
+    pub song_id: ObjectID,
+    pub kiosk_id: ObjectID,
+    pub available: bool,
+    pub tx_digest: TransactionDigest,
+    pub timestamp_ms: Option<u64>,
+}
+
+pub(crate) fn open(path: &Path) -> Result<Connection> {
+    let conn = Connection::open(path)?;
+    conn.execute_batch(SCHEMA)?;
+
+    Ok(conn)
+}
+
+pub(crate) fn get_cursor(conn: &Connection) -> Result<Option<EventID>> {
+    let cursor = conn.query_row(
+        "SELECT tx_digest, event_seq FROM cursor WHERE id = 0",
+        [],
+        |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    ).optional()?;
+
+    let Some((tx_digest, event_seq)) = cursor else {
+        return Ok(None);
+    };
+
+    Ok(Some(EventID {
+        tx_digest: TransactionDigest::from_str(&tx_digest)?,
+        event_seq: event_seq as u64
+    }))
+}
+
+pub(crate) fn set_cursor(tx: &Transaction, cursor: &EventID) -> Result<()> {
+    tx.execute(
+        "INSERT INTO cursor (id, tx_digest, event_seq) VALUES (0, ?1, ?2)
+         ON CONFLICT (id) DO UPDATE SET tx_digest = ?1, event_seq = ?2",
+        params![cursor.tx_digest.to_string(), cursor.event_seq as i64]
+    )?;
+
+    Ok(())
+}
+
+pub(crate) fn apply_event(
+    tx: &Transaction,
+    event: &TunoEvent,
+    tx_digest: &TransactionDigest,
+    timestamp_ms: Option<u64>
+) -> Result<()> {
+    let timestamp_ms = timestamp_ms.map(|t| t as i64);
+    match event {
+        TunoEvent::SongCreated { id, owner, title, artist, streaming_price } => {
+            tx.execute(
+                "INSERT OR IGNORE INTO songs (id, owner, title, artist, streaming_price, created_at)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    id.to_string(),
+                    owner.to_string(),
+                    title,
+                    artist,
+                    *streaming_price as i64,
+                    timestamp_ms
+                ]
+            )?;
+        },
+        TunoEvent::SongBecameAvailable { id, kiosk_id, streaming_price } => {
+            tx.execute(
+                "UPDATE songs SET kiosk_id = ?2 WHERE id = ?1",
+                params![id.to_string(), kiosk_id.to_string()]
+            )?;
+            tx.execute(
+                "INSERT INTO availability (song_id, kiosk_id, available, streaming_price, tx_digest, timestamp_ms)
+                 VALUES (?1, ?2, 1, ?3, ?4, ?5)",
+                params![
+                    id.to_string(),
+                    kiosk_id.to_string(),
+                    *streaming_price as i64,
+                    tx_digest.to_string(),
+                    timestamp_ms
+                ]
+            )?;
+        },
+        TunoEvent::SongBecameUnavailable { id, kiosk_id } => {
+            tx.execute(
+                "UPDATE songs SET kiosk_id = NULL WHERE id = ?1",
+                params![id.to_string()]
+            )?;
+            tx.execute(
+                "INSERT INTO availability (song_id, kiosk_id, available, tx_digest, timestamp_ms)
+                 VALUES (?1, ?2, 0, ?3, ?4)",
+                params![id.to_string(), kiosk_id.to_string(), tx_digest.to_string(), timestamp_ms]
+            )?;
+        },
+        TunoEvent::SongUpdated { id, title, artist, streaming_price } => {
+            tx.execute(
+                "UPDATE songs SET title = ?2, artist = ?3, streaming_price = ?4 WHERE id = ?1",
+                params![id.to_string(), title, artist, *streaming_price as i64]
+            )?;
+        },
+        TunoEvent::DistributorAdded { song_id, distributor, url, streaming_price } => {
+            tx.execute(
+                "INSERT INTO distributors (song_id, distributor, url, streaming_price)
+                 VALUES (?1, ?2, ?3, ?4)
+                 ON CONFLICT (song_id, distributor) DO UPDATE SET url = ?3, streaming_price = ?4",
+                params![song_id.to_string(), distributor.to_string(), url, *streaming_price as i64]
+            )?;
+        },
+        TunoEvent::DistributorRemoved { song_id, distributor } => {
+            tx.execute(
+                "DELETE FROM distributors WHERE song_id = ?1 AND distributor = ?2",
+                params![song_id.to_string(), distributor.to_string()]
+            )?;
+        },
+    }
+
+    Ok(())
+}
+
+pub(crate) fn query_songs_by_owner(
+    conn: &Connection,
+    owner: &IotaAddress,
+    cursor: Option<&ObjectID>,
+    limit: Option<usize>
+) -> Result<Vec<IndexedSong>> {
+    let mut stmt = conn.prepare(
+        "SELECT id, owner, title, artist, streaming_price, kiosk_id FROM songs
+         WHERE owner = ?1 AND rowid > COALESCE((SELECT rowid FROM songs WHERE id = ?2), 0)
+         ORDER BY rowid LIMIT ?3"
+    )?;
+
+    let rows = stmt.query_map(params![
+        owner.to_string(),
+        cursor.map(|c| c.to_string()),
+        limit.map_or(-1, |l| l as i64)
+    ], |row| Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, String>(3)?,
+        row.get::<_, i64>(4)?,
+        row.get::<_, Option<String>>(5)?,
+    )))?;
+
+    let mut songs = vec![];
+    for row in rows {
+        let (id, owner, title, artist, streaming_price, kiosk_id) = row?;
+        songs.push(IndexedSong {
+            id: ObjectID::from_hex_literal(&id)?,
+            owner: IotaAddress::from_str(&owner)?,
+            title,
+            artist,
+            streaming_price: streaming_price as u64,
+            kiosk_id: kiosk_id.map(|k| ObjectID::from_hex_literal(&k)).transpose()?
+        });
+    }
+
+    Ok(songs)
+}
+
+pub(crate) fn query_song_kiosk(conn: &Connection, song: &ObjectID) -> Result<Option<ObjectID>> {
+    let kiosk_id = conn.query_row(
+        "SELECT kiosk_id FROM songs WHERE id = ?1",
+        params![song.to_string()],
+        |row| row.get::<_, Option<String>>(0)
+    ).optional()?.flatten();
+
+    Ok(kiosk_id.map(|k| ObjectID::from_hex_literal(&k)).transpose()?)
+}
+
+pub(crate) fn query_distributors(
+    conn: &Connection,
+    column: &str,
+    value: &str
+) -> Result<Vec<IndexedDistributor>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT song_id, distributor, url, streaming_price FROM distributors
+         WHERE {column} = ?1 ORDER BY rowid"
+    ))?;
+
+    let rows = stmt.query_map(params![value], |row| Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, String>(2)?,
+        row.get::<_, i64>(3)?,
+    )))?;
+
+    let mut distributors = vec![];
+    for row in rows {
+        let (song_id, distributor, url, streaming_price) = row?;
+        distributors.push(IndexedDistributor {
+            song_id: ObjectID::from_hex_literal(&song_id)?,
+            distributor: IotaAddress::from_str(&distributor)?,
+            url,
+            streaming_price: streaming_price as u64
+        });
+    }
+
+    Ok(distributors)
+}
+
+pub(crate) fn query_availability_changes(
+    conn: &Connection,
+    song: &ObjectID
+) -> Result<Vec<AvailabilityChange>> {
+    let mut stmt = conn.prepare(
+        "SELECT song_id, kiosk_id, available, tx_digest, timestamp_ms FROM availability
+         WHERE song_id = ?1 ORDER BY rowid"
+    )?;
+
+    let rows = stmt.query_map(params![song.to_string()], |row| Ok((
+        row.get::<_, String>(0)?,
+        row.get::<_, String>(1)?,
+        row.get::<_, bool>(2)?,
+        row.get::<_, String>(3)?,
+        row.get::<_, Option<i64>>(4)?,
+    )))?;
+
+    let mut changes = vec![];
+    for row in rows {
+        let (song_id, kiosk_id, available, tx_digest, timestamp_ms) = row?;
+        changes.push(AvailabilityChange {
+            song_id: ObjectID::from_hex_literal(&song_id)?,
+            kiosk_id: ObjectID::from_hex_literal(&kiosk_id)?,
+            available,
+            tx_digest: TransactionDigest::from_str(&tx_digest)?,
+            timestamp_ms: timestamp_ms.map(|t| t as u64)
+        });
+    }
+
+    Ok(changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(conn: &mut Connection, event: TunoEvent) {
+        let tx = conn.transaction().unwrap();
+        apply_event(&tx, &event, &TransactionDigest::random(), None).unwrap();
+        tx.commit().unwrap();
+    }
+
+    #[test]
+    fn song_kiosk_follows_availability() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(SCHEMA).unwrap();
+
+        let (id, kiosk_id) = (ObjectID::random(), ObjectID::random());
+        assert_eq!(query_song_kiosk(&conn, &id).unwrap(), None);
+
+        apply(&mut conn, TunoEvent::SongCreated {
+            id,
+            owner: IotaAddress::random_for_testing_only(),
+            title: "Test Song".to_string(),
+            artist: "Test Artist".to_string(),
+            streaming_price: 10
+        });
+        assert_eq!(query_song_kiosk(&conn, &id).unwrap(), None);
+
+        apply(&mut conn, TunoEvent::SongBecameAvailable { id, kiosk_id, streaming_price: 10 });
+        assert_eq!(query_song_kiosk(&conn, &id).unwrap(), Some(kiosk_id));
+
+        apply(&mut conn, TunoEvent::SongBecameUnavailable { id, kiosk_id });
+        assert_eq!(query_song_kiosk(&conn, &id).unwrap(), None);
+    }
+}
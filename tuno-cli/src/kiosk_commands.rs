@@ -1,16 +1,94 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
-use iota_sdk::types::base_types::ObjectID;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
 
-use crate::client::{Client, Connection};
+use crate::client::{Client, Connection, CreatorSetup, KioskArgs, Pagination};
+use crate::displays::{KioskReport, KioskTransaction, Listing, OwnedCapabilities};
+use crate::profiles::{Profile, Profiles};
+use crate::output::{print_output, OutputFormat};
+use crate::utils::confirm;
 
 #[derive(Parser)]
 pub enum KioskCommands {
     /// List all songs available in kiosk
     List {
-        /// Kiosk's object id
+        /// Kiosk's object id (default: the profile's, or the only one owned)
         #[arg(long, env = "KIOSK")]
-        kiosk: ObjectID,
+        kiosk: Option<ObjectID>,
+
+        #[command(flatten)]
+        pagination: Pagination,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Show kiosk's owner, item count and profits, and the displays of songs that no longer exist
+    Info {
+        /// Kiosk's object id (default: the profile's, or the only one owned)
+        #[arg(long, env = "KIOSK")]
+        kiosk: Option<ObjectID>,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Withdraw kiosk's profits to the active address
+    Withdraw {
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        /// Amount to withdraw, in NANOS (default: all profits)
+        #[arg(long)]
+        amount: Option<u64>,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Take an item out of kiosk, deleting song displays their song no longer refers to
+    RemoveItem {
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        /// Item's object id
+        #[arg(long)]
+        item: ObjectID,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// List the creator and kiosk owner capabilities of the active address
+    Discover {
+        /// Save the creator capability and kiosk as a new profile under this name
+        #[arg(long)]
+        save: Option<String>,
+
+        /// Creator capability to save (default: the only one owned)
+        #[arg(long, requires = "save")]
+        cap: Option<ObjectID>,
+
+        /// Kiosk to save
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Transfer kiosk's ownership, along with its owner capability, and remove the profiles using it
+    TransferCap {
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        /// Address of the new owner
+        #[arg(long)]
+        recipient: IotaAddress,
+
+        /// Transfer without confirming
+        #[arg(long, short)]
+        yes: bool,
 
         #[command(flatten)]
         conn: Connection
@@ -18,18 +96,140 @@ pub enum KioskCommands {
 }
 
 impl KioskCommands {
-    pub async fn execute(self) -> Result<()> {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
         match self {
             KioskCommands::List {
                 kiosk,
+                pagination,
                 conn
             } => {
-                let client = Client::new(conn)?;
-                let songs = client.get_kiosk_songs(kiosk).await?;
+                let client = Client::connect(conn).await?;
+                let kiosk = client.kiosk_or_owned(kiosk).await?;
+                let (
+                    songs,
+                    skipped,
+                    next_cursor
+                ) = client.get_kiosk_songs(kiosk, &pagination).await?;
+
+                print_output(&Listing {
+                    items: songs,
+                    skipped: skipped.iter().map(ToString::to_string).collect(),
+                    next_cursor
+                }, output)
+            }
+
+            KioskCommands::Info {
+                kiosk,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let kiosk = client.kiosk_or_owned(kiosk).await?;
+                let info = client.get_kiosk(kiosk).await?;
+                let orphans = client.get_orphan_displays(kiosk).await?.into_iter().collect();
+
+                print_output(&KioskReport { info, orphans }, output)
+            }
+
+            KioskCommands::Withdraw {
+                kiosk_args,
+                amount,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
+                let digest = client.withdraw_kiosk_profits(&owned_kiosk, amount).await?;
+
+                print_output(&KioskTransaction {
+                    kiosk: owned_kiosk.kiosk,
+                    digest,
+                    action: "profits withdrawn".to_string()
+                }, output)
+            }
+
+            KioskCommands::RemoveItem {
+                kiosk_args,
+                item,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
+                let digest = client.remove_kiosk_item(&owned_kiosk, item).await?;
+
+                print_output(&KioskTransaction {
+                    kiosk: owned_kiosk.kiosk,
+                    digest,
+                    action: format!("item {item} removed")
+                }, output)
+            }
+
+            KioskCommands::Discover {
+                save,
+                cap,
+                kiosk_args,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+
+                if let Some(name) = save {
+                    let mut profiles = Profiles::load(&client.network)?;
+                    if profiles.contains(&name) {
+                        bail!("Profile {name} already exists on {}, choose another name with --save", client.network);
+                    }
+
+                    let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
+                    let setup = CreatorSetup {
+                        cap: client.resolve_creator_cap(cap).await?,
+                        kiosk: owned_kiosk.kiosk,
+                        kiosk_cap: owned_kiosk.kiosk_cap
+                    };
+
+                    profiles.insert(name.clone(), Profile { address: client.address, setup });
+                    let location = profiles.save()?;
+                    eprintln!("Profile {name} saved to {}, select it with --profile {name}", location.display());
+                }
+
+                print_output(&OwnedCapabilities {
+                    creator_caps: client.get_creator_caps().await?,
+                    kiosk_caps: client.get_kiosk_caps().await?
+                }, output)
+            }
+
+            KioskCommands::TransferCap {
+                kiosk_args,
+                recipient,
+                yes,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
+
+                if !yes && !confirm(&format!("Transfer kiosk {} to {recipient}?", owned_kiosk.kiosk))? {
+                    eprintln!("Transfer cancelled");
+                    return Ok(());
+                }
+
+                let digest = client.transfer_kiosk_cap(&owned_kiosk, recipient).await?;
+
+                // The transfer is done, profiles that can no longer be used are only worth a warning
+                let removed = Profiles::load(&client.network).and_then(|mut profiles| {
+                    let removed = profiles.remove_kiosk(&owned_kiosk.kiosk);
+                    if !removed.is_empty() {
+                        profiles.save()?;
+                    }
+
+                    Ok(removed)
+                });
+                match removed {
+                    Ok(removed) if removed.is_empty() => {},
+                    Ok(removed) => eprintln!("Removed profile(s) using the transferred kiosk: {}", removed.join(", ")),
+                    Err(e) => eprintln!("Profiles using kiosk {} were not removed: {e:#}", owned_kiosk.kiosk)
+                }
 
-                println!("{}", songs);
-                
-                Ok(())
+                print_output(&KioskTransaction {
+                    kiosk: owned_kiosk.kiosk,
+                    digest,
+                    action: format!("transferred to {recipient}")
+                }, output)
             }
         }
     }
@@ -3,9 +3,22 @@ pub mod tuno_commands;
 pub(crate) mod distribution_commands;
 pub(crate) mod music_commands;
 pub(crate) mod kiosk_commands;
+pub(crate) mod wallet_commands;
+pub(crate) mod profile_commands;
+pub(crate) mod tui;
+pub(crate) mod player;
 pub mod client;
 pub(crate) mod utils;
+pub(crate) mod gas_pool;
 pub(crate) mod local_storage;
+pub mod media;
+pub(crate) mod manifest;
+pub(crate) mod profiles;
+pub mod indexer;
 pub(crate) mod displays;
+pub mod output;
 pub(crate) mod types;
+pub mod objects;
 pub(crate) mod constants;
+pub(crate) mod compat;
+pub mod errors;
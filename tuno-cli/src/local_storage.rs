@@ -1,35 +1,47 @@
 use std::io::BufReader;
 use std::{fs, path::PathBuf};
-use anyhow::{bail, Result};
+use anyhow::Result;
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use log::warn;
+use serde::Serialize;
 
-use symphonia::default::get_probe;
-use symphonia::core::io::MediaSourceStream;
-use symphonia::core::probe::Hint;
+use symphonia::core::meta::{MetadataRevision, StandardTagKey, StandardVisualKey};
 
 use iota_sdk::types::programmable_transaction_builder::ProgrammableTransactionBuilder;
 use iota_sdk::types::transaction::Argument;
 
-use crate::constants::DEFAULT_MEDIA_STORAGE;
+use crate::constants::{DEFAULT_MEDIA_STORAGE, MAX_INLINE_COVER_ART_SIZE};
+use crate::media::{probe, MediaError, MediaFormat, MediaInfo};
 use crate::types::TunoSignature;
 
 #[derive(Debug)]
 pub struct FileMetadata {
+    pub(crate) format: MediaFormat,
     length: usize,
     duration: usize,
     signature: Vec<Vec<u8>>,
 }
 
-impl From<&PathBuf> for FileMetadata {
-    fn from(path: &PathBuf) -> Self {
-        Self {
-            length: fs::metadata(path).expect("Error parsing metadata").len() as usize,
-            duration: compute_duration(path).expect("Error computing duration") as usize,
-            signature: TunoSignature::from(path).sig
-        }
+impl TryFrom<&PathBuf> for FileMetadata {
+    type Error = MediaError;
+
+    fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
+        let info = MediaInfo::probe(path)?;
+        Ok(Self {
+            format: info.format,
+            length: info.size as usize,
+            duration: info.duration as usize,
+            signature: TunoSignature::try_from(path)?.sig
+        })
     }
 }
 
 impl FileMetadata {
+    /// Approximate size of the metadata once serialized as transaction arguments
+    pub(crate) fn args_size(&self) -> usize {
+        2 * 8 + self.signature.iter().map(|s| s.len() + 1).sum::<usize>() + 4
+    }
+
     pub(crate) fn as_arguments(
         &self,
         ptb: &mut ProgrammableTransactionBuilder
@@ -42,36 +54,138 @@ impl FileMetadata {
     }
 }
 
-fn compute_duration(path: &PathBuf) -> Result<u64> {
-    let file = fs::File::open(path).expect("failed to open media");
-    let probe_result = get_probe().format(
-        &Hint::new().with_extension("mp3"),
-        MediaSourceStream::new(
-            Box::new(file),
-            Default::default()
-        ),
-        &Default::default(),
-        &Default::default()
-    ).expect("Error probing file");
-
-    let track = probe_result.format
-        .default_track().expect("no default track");
-
-    if let Some(n_frames) = track.codec_params.n_frames {
-        if let Some(sample_rate) = track.codec_params.sample_rate {
-            return Ok((n_frames * 1000) / sample_rate as u64);
+/// Song metadata found in the tags of a media file
+#[derive(Default)]
+pub(crate) struct TrackTags {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub release_year: Option<u64>,
+    pub genre: Option<String>,
+    pub cover_art: Option<CoverArt>,
+}
+
+pub(crate) struct CoverArt {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+impl TrackTags {
+    /// Embedded cover art as a data URL, if small enough to be stored on chain
+    pub(crate) fn cover_art_url(&self) -> Option<String> {
+        let cover = self.cover_art.as_ref()?;
+        if cover.data.len() > MAX_INLINE_COVER_ART_SIZE {
+            warn!("Embedded cover art is too large to be inlined ({} bytes)", cover.data.len());
+            return None;
         }
+
+        Some(format!("data:{};base64,{}", cover.media_type, STANDARD.encode(&cover.data)))
     }
 
-    bail!("Error extracting codec parameters")
+    /// Fills the fields that are still missing with the revision's tags
+    fn apply(&mut self, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let value = tag.value.to_string();
+            let field = match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => &mut self.title,
+                Some(StandardTagKey::Artist) => &mut self.artist,
+                Some(StandardTagKey::Album) => &mut self.album,
+                Some(StandardTagKey::Genre) => &mut self.genre,
+                Some(
+                    StandardTagKey::Date
+                    | StandardTagKey::ReleaseDate
+                    | StandardTagKey::OriginalDate
+                ) => {
+                    if self.release_year.is_none() {
+                        self.release_year = value.get(..4).and_then(|y| y.parse().ok());
+                    }
+                    continue;
+                },
+                _ => continue
+            };
+
+            if field.is_none() && !value.trim().is_empty() {
+                *field = Some(value.trim().to_string());
+            }
+        }
+
+        if self.cover_art.is_none() {
+            let visuals = revision.visuals();
+            self.cover_art = visuals.iter()
+                .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+                .or(visuals.first())
+                .map(|v| CoverArt {
+                    media_type: v.media_type.clone(),
+                    data: v.data.to_vec()
+                });
+        }
+    }
+}
+
+pub(crate) fn read_tags(path: &PathBuf) -> Result<TrackTags> {
+    let mut probe_result = probe(path, MediaFormat::detect(path)?)?;
+    let mut tags = TrackTags::default();
+
+    if let Some(metadata) = probe_result.metadata.get() {
+        if let Some(revision) = metadata.current() {
+            tags.apply(revision);
+        }
+    }
+
+    if let Some(revision) = probe_result.format.metadata().current() {
+        tags.apply(revision);
+    }
+
+    Ok(tags)
 }
 
 pub(crate) fn get_local_song_reader(hex_id: &str) -> Result<BufReader<fs::File>> {
+    Ok(BufReader::new(fs::File::open(get_local_song_location(hex_id))?))
+}
+
+/// Format of a stored song, detected from its content
+pub(crate) fn get_local_song_format(hex_id: &str) -> Result<MediaFormat> {
+    MediaFormat::detect(&get_local_song_location(hex_id))
+}
+
+/// State of the stored copy of a song
+#[derive(Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum LocalCopy {
+    Missing,
+    Verified,
+    Mismatched,
+}
+
+impl std::fmt::Display for LocalCopy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Missing => "not stored",
+            Self::Verified => "stored, signature verified",
+            Self::Mismatched => "stored, signature mismatch",
+        })
+    }
+}
+
+/// Checks the stored copy of a song against its on-chain signature
+pub(crate) fn check_local_song(hex_id: &str, signature: &TunoSignature) -> Result<LocalCopy> {
+    let location = get_local_song_location(hex_id);
+    if !location.is_file() {
+        return Ok(LocalCopy::Missing);
+    }
+
+    Ok(match &TunoSignature::try_from(&location)? == signature {
+        true => LocalCopy::Verified,
+        false => LocalCopy::Mismatched
+    })
+}
+
+fn get_local_song_location(hex_id: &str) -> PathBuf {
     let (p, f) = hex_id.split_at(2);
     let mut location = PathBuf::from(DEFAULT_MEDIA_STORAGE);
 
     location.extend([p, f]);
-    Ok(BufReader::new(fs::File::open(location)?))
+    location
 }
 
 pub(crate) fn get_all_song_ids() -> Result<Vec<String>> {
@@ -1,7 +1,11 @@
+use std::process;
 use dotenv::dotenv;
+use env_logger::Env;
 use anyhow::Result;
 use clap::Parser;
 
+use tuno_cli::errors::Error;
+use tuno_cli::output::OutputFormat;
 use tuno_cli::tuno_commands::TunoCommands;
 
 #[derive(Parser)]
@@ -10,6 +14,10 @@ use tuno_cli::tuno_commands::TunoCommands;
     about = "Client for interacting with the Tuno Media network",
 )]
 struct Args {
+    /// Format of the command's result
+    #[arg(long, short, global = true, value_enum, default_value_t)]
+    output: OutputFormat,
+
     #[command(subcommand)]
     command: TunoCommands,
 }
@@ -18,7 +26,18 @@ struct Args {
 async fn main() -> Result<()> {
     dotenv().ok();
     let args = Args::parse();
-    env_logger::init();
-    args.command.execute().await?;
+    // Warnings of the library, such as package mismatches, are shown unless RUST_LOG says otherwise
+    env_logger::Builder::from_env(Env::default().default_filter_or("warn")).init();
+    if let Err(e) = args.command.execute(args.output).await {
+        eprintln!("Error: {e:?}");
+
+        let error = Error::from(e);
+        if let Some(hint) = error.hint() {
+            eprintln!("Hint: {hint}");
+        }
+
+        process::exit(error.exit_code());
+    }
+
     Ok(())
 }
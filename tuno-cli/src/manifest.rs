@@ -0,0 +1,130 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::{bail, Result};
+use clap::Parser;
+use serde::Deserialize;
+
+use crate::client::SongMetadata;
+use crate::local_storage::TrackTags;
+use crate::media::is_media_file;
+
+/// Metadata shared by every track of a batch, used when a track doesn't define its own
+#[derive(Parser)]
+pub struct TrackDefaults {
+    /// Artist's name
+    #[arg(long)]
+    artist: Option<String>,
+    /// Album name
+    #[arg(long)]
+    album: Option<String>,
+    /// Release year
+    #[arg(long)]
+    release_year: Option<u64>,
+    /// Genre
+    #[arg(long)]
+    genre: Option<String>,
+    /// Url for cover art
+    #[arg(long)]
+    cover_art_url: Option<String>,
+    /// Price for streaming entire song
+    #[arg(long)]
+    streaming_price: Option<u64>,
+}
+
+/// Track as described by a manifest row, file paths are relative to the manifest
+#[derive(Deserialize)]
+pub(crate) struct ManifestTrack {
+    pub file: PathBuf,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub release_year: Option<u64>,
+    pub genre: Option<String>,
+    pub cover_art_url: Option<String>,
+    pub streaming_price: Option<u64>,
+}
+
+#[derive(Deserialize)]
+struct Manifest {
+    tracks: Vec<ManifestTrack>,
+}
+
+impl ManifestTrack {
+    fn from_file(file: PathBuf) -> Self {
+        Self {
+            file,
+            title: None,
+            artist: None,
+            album: None,
+            release_year: None,
+            genre: None,
+            cover_art_url: None,
+            streaming_price: None,
+        }
+    }
+
+    /// Completes the track's metadata with `defaults`, then with the file's `tags`,
+    /// failing if a field is defined by none of them
+    pub(crate) fn resolve(
+        self,
+        defaults: &TrackDefaults,
+        tags: TrackTags
+    ) -> Result<(PathBuf, SongMetadata)> {
+        let file = self.file;
+        let missing = |field: &str| anyhow::anyhow!("No {field} defined for {}", file.display());
+        let cover_art_url = tags.cover_art_url();
+
+        let song_md = SongMetadata {
+            title: self.title
+                .or(tags.title)
+                .or(file.file_stem().map(|s| s.to_string_lossy().to_string()))
+                .ok_or_else(|| missing("title"))?,
+            artist: self.artist.or(defaults.artist.clone()).or(tags.artist).ok_or_else(|| missing("artist"))?,
+            album: self.album.or(defaults.album.clone()).or(tags.album).ok_or_else(|| missing("album"))?,
+            release_year: self.release_year.or(defaults.release_year).or(tags.release_year)
+                .ok_or_else(|| missing("release year"))?,
+            genre: self.genre.or(defaults.genre.clone()).or(tags.genre).ok_or_else(|| missing("genre"))?,
+            cover_art_url: self.cover_art_url.or(defaults.cover_art_url.clone()).or(cover_art_url)
+                .ok_or_else(|| missing("cover art url"))?,
+            streaming_price: self.streaming_price.or(defaults.streaming_price).ok_or_else(|| missing("streaming price"))?,
+        };
+
+        Ok((file, song_md))
+    }
+}
+
+/// Reads the tracks of a CSV, TOML or JSON manifest
+pub(crate) fn read_manifest(path: &Path) -> Result<Vec<ManifestTrack>> {
+    let content = fs::read_to_string(path)?;
+    let mut tracks = match path.extension().and_then(|e| e.to_str()) {
+        Some("csv") => csv::Reader::from_reader(content.as_bytes())
+            .deserialize()
+            .collect::<Result<Vec<ManifestTrack>, _>>()?,
+        Some("toml") => toml::from_str::<Manifest>(&content)?.tracks,
+        Some("json") => serde_json::from_str::<Manifest>(&content)?.tracks,
+        _ => bail!("Unsupported manifest format: {}", path.display())
+    };
+
+    let root = path.parent().unwrap_or(Path::new("."));
+    for track in &mut tracks {
+        track.file = root.join(&track.file);
+    }
+
+    Ok(tracks)
+}
+
+/// Lists the supported media files of a directory
+pub(crate) fn read_directory(dir: &Path) -> Result<Vec<ManifestTrack>> {
+    let mut files = fs::read_dir(dir)?
+        .map(|entry| Ok(entry?.path()))
+        .collect::<Result<Vec<_>>>()?;
+    files.sort();
+
+    Ok(
+        files.into_iter()
+            .filter(|f| is_media_file(f))
+            .map(ManifestTrack::from_file)
+            .collect()
+    )
+}
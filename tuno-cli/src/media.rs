@@ -0,0 +1,374 @@
+use std::fmt::{Display, Formatter};
+use std::io::{self, ErrorKind, Read};
+use std::path::{Path, PathBuf};
+use std::fs;
+use serde::Serialize;
+use thiserror::Error;
+
+use symphonia::default::{get_codecs, get_probe};
+use symphonia::core::codecs::CodecParameters;
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::probe::{Hint, ProbeResult};
+use symphonia::core::units::TimeBase;
+
+use crate::constants::TUNO_BASE_CHUNK_SIZE;
+
+/// Why a media file could not be used
+#[derive(Debug, Error)]
+pub enum MediaError {
+    #[error("File not found: {0}")]
+    NotFound(PathBuf),
+    #[error("Could not read {path}: {source}")]
+    Unreadable { path: PathBuf, source: io::Error },
+    #[error("Unsupported media format: {0} (expected MP3, FLAC, Ogg Vorbis/Opus, AAC/M4A or WAV)")]
+    UnsupportedFormat(PathBuf),
+    #[error("Unsupported codec: {0} songs can be published and distributed, but not decoded for playback")]
+    UnsupportedCodec(&'static str),
+    #[error("Corrupted media stream in {path}: {reason}")]
+    Corrupted { path: PathBuf, reason: String },
+}
+
+impl MediaError {
+    pub(crate) fn io(path: &Path, source: io::Error) -> Self {
+        match source.kind() {
+            ErrorKind::NotFound => Self::NotFound(path.to_path_buf()),
+            _ => Self::Unreadable { path: path.to_path_buf(), source }
+        }
+    }
+
+    fn decoding(path: &Path, error: SymphoniaError) -> Self {
+        match error {
+            SymphoniaError::IoError(source) => Self::io(path, source),
+            SymphoniaError::Unsupported(_) => Self::UnsupportedFormat(path.to_path_buf()),
+            e => Self::Corrupted { path: path.to_path_buf(), reason: e.to_string() }
+        }
+    }
+}
+
+pub(crate) fn open(path: &Path) -> Result<fs::File, MediaError> {
+    fs::File::open(path).map_err(|e| MediaError::io(path, e))
+}
+
+/// Audio formats that can be published and distributed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum MediaFormat {
+    Mp3,
+    Flac,
+    Vorbis,
+    Opus,
+    Aac,
+    M4a,
+    Wav,
+}
+
+impl MediaFormat {
+    /// Number of leading bytes needed to recognize a format
+    const SNIFF_SIZE: usize = 64;
+
+    /// Detects the format of a file from its content, falling back on its extension
+    pub(crate) fn detect(path: &Path) -> Result<Self, MediaError> {
+        let mut header = Vec::with_capacity(Self::SNIFF_SIZE);
+        open(path)?
+            .take(Self::SNIFF_SIZE as u64)
+            .read_to_end(&mut header)
+            .map_err(|e| MediaError::io(path, e))?;
+
+        if let Some(format) = Self::from_content(&header) {
+            return Ok(format);
+        }
+
+        match path.extension().and_then(|e| e.to_str()).and_then(Self::from_extension) {
+            Some(format) => Ok(format),
+            None => Err(MediaError::UnsupportedFormat(path.to_path_buf()))
+        }
+    }
+
+    /// Recognizes a format from the magic bytes at the start of a file
+    pub(crate) fn from_content(header: &[u8]) -> Option<Self> {
+        match header {
+            [b'I', b'D', b'3', ..] => Some(Self::Mp3),
+            [b'f', b'L', b'a', b'C', ..] => Some(Self::Flac),
+            [b'R', b'I', b'F', b'F', _, _, _, _, b'W', b'A', b'V', b'E', ..] => Some(Self::Wav),
+            [_, _, _, _, b'f', b't', b'y', b'p', ..] => Some(Self::M4a),
+            [b'O', b'g', b'g', b'S', ..] => {
+                if header.windows(8).any(|w| w == b"OpusHead") {
+                    Some(Self::Opus)
+                } else if header.windows(7).any(|w| w == b"\x01vorbis") {
+                    Some(Self::Vorbis)
+                } else {
+                    None
+                }
+            },
+            // ADTS frames have a 12-bit sync word and a zero layer
+            [0xFF, b, ..] if b & 0xF6 == 0xF0 => Some(Self::Aac),
+            // MPEG audio frames have an 11-bit sync word and a non-zero layer
+            [0xFF, b, ..] if b & 0xE0 == 0xE0 && b & 0x06 != 0 => Some(Self::Mp3),
+            _ => None
+        }
+    }
+
+    pub(crate) fn from_extension(extension: &str) -> Option<Self> {
+        match extension.to_ascii_lowercase().as_str() {
+            "mp3" => Some(Self::Mp3),
+            "flac" => Some(Self::Flac),
+            "ogg" | "oga" => Some(Self::Vorbis),
+            "opus" => Some(Self::Opus),
+            "aac" => Some(Self::Aac),
+            "m4a" | "mp4" => Some(Self::M4a),
+            "wav" => Some(Self::Wav),
+            _ => None
+        }
+    }
+
+    pub(crate) fn extension(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "mp3",
+            Self::Flac => "flac",
+            Self::Vorbis => "ogg",
+            Self::Opus => "opus",
+            Self::Aac => "aac",
+            Self::M4a => "m4a",
+            Self::Wav => "wav",
+        }
+    }
+
+    /// MIME type telling players how to decode the file
+    pub(crate) fn mime_type(&self) -> &'static str {
+        match self {
+            Self::Mp3 => "audio/mpeg",
+            Self::Flac => "audio/flac",
+            Self::Vorbis => "audio/ogg; codecs=vorbis",
+            Self::Opus => "audio/ogg; codecs=opus",
+            Self::Aac => "audio/aac",
+            Self::M4a => "audio/mp4",
+            Self::Wav => "audio/wav",
+        }
+    }
+
+    pub(crate) fn hint(&self) -> Hint {
+        let mut hint = Hint::new();
+        hint.with_extension(self.extension())
+            .mime_type(self.mime_type());
+
+        hint
+    }
+}
+
+impl Display for MediaFormat {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", match self {
+            Self::Mp3 => "MP3",
+            Self::Flac => "FLAC",
+            Self::Vorbis => "Ogg Vorbis",
+            Self::Opus => "Ogg Opus",
+            Self::Aac => "AAC",
+            Self::M4a => "AAC/M4A",
+            Self::Wav => "WAV",
+        })
+    }
+}
+
+/// Whether a file looks like a supported media file from its extension
+pub(crate) fn is_media_file(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .and_then(MediaFormat::from_extension)
+        .is_some()
+}
+
+/// Technical description of a media file
+#[derive(Serialize)]
+pub(crate) struct MediaInfo {
+    pub format: MediaFormat,
+    pub codec: String,
+    pub sample_rate: Option<u32>,
+    pub channels: Option<usize>,
+    /// Duration in milliseconds
+    pub duration: u64,
+    /// Size in bytes
+    pub size: u64,
+}
+
+impl MediaInfo {
+    pub(crate) fn probe(path: &Path) -> Result<Self, MediaError> {
+        let format = MediaFormat::detect(path)?;
+        let mut probe_result = probe(path, format)?;
+
+        let Some(track) = probe_result.format.default_track() else {
+            return Err(MediaError::Corrupted {
+                path: path.to_path_buf(),
+                reason: "no audio track".to_string()
+            });
+        };
+
+        let track_id = track.id;
+        let params = track.codec_params.clone();
+
+        Ok(Self {
+            format,
+            codec: get_codecs().get_codec(params.codec)
+                .map(|d| d.long_name.to_string())
+                .unwrap_or_else(|| format.to_string()),
+            sample_rate: params.sample_rate,
+            channels: params.channels.map(|c| c.count()),
+            duration: compute_duration(path, probe_result.format.as_mut(), track_id, &params)?,
+            size: fs::metadata(path).map_err(|e| MediaError::io(path, e))?.len(),
+        })
+    }
+
+    /// Average bitrate in kbit/s
+    pub(crate) fn bitrate(&self) -> Option<u64> {
+        (self.duration > 0).then(|| self.size * 8 / self.duration)
+    }
+
+    /// Number of chunks the file is signed and distributed in
+    pub(crate) fn chunks(&self) -> u64 {
+        self.size.div_ceil(TUNO_BASE_CHUNK_SIZE as u64)
+    }
+}
+
+pub(crate) fn probe(path: &Path, format: MediaFormat) -> Result<ProbeResult, MediaError> {
+    let file = open(path)?;
+    get_probe().format(
+        &format.hint(),
+        MediaSourceStream::new(
+            Box::new(file),
+            Default::default()
+        ),
+        // Trims encoder delay and padding so that durations are exact
+        &FormatOptions { enable_gapless: true, ..Default::default() },
+        &Default::default()
+    ).map_err(|e| MediaError::decoding(path, e))
+}
+
+/// Duration of a track in milliseconds
+fn compute_duration(
+    path: &Path,
+    reader: &mut dyn FormatReader,
+    track_id: u32,
+    params: &CodecParameters
+) -> Result<u64, MediaError> {
+    if let (Some(n_frames), Some(sample_rate)) = (params.n_frames, params.sample_rate) {
+        return Ok((n_frames * 1000) / sample_rate as u64);
+    }
+
+    // VBR files without a frame count in their headers: scan the packets' timestamps
+    let Some(time_base) = params.time_base
+        .or(params.sample_rate.map(|rate| TimeBase::new(1, rate))) else {
+        return Err(MediaError::Corrupted {
+            path: path.to_path_buf(),
+            reason: "missing sample rate".to_string()
+        });
+    };
+
+    let mut end = 0;
+    loop {
+        match reader.next_packet() {
+            Ok(packet) if packet.track_id() == track_id => end = end.max(packet.ts + packet.dur),
+            Ok(_) => continue,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(MediaError::decoding(path, e))
+        }
+    }
+
+    let time = time_base.calc_time(end);
+    Ok(time.seconds * 1000 + (time.frac * 1000.0) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use hound::{SampleFormat, WavSpec, WavWriter};
+
+    use super::*;
+
+    fn wav(sample_rate: u32, samples: usize) -> Vec<u8> {
+        let mut wav = Cursor::new(vec![]);
+        let mut writer = WavWriter::new(&mut wav, WavSpec {
+            channels: 1,
+            sample_rate,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        }).unwrap();
+        for i in 0..samples {
+            writer.write_sample(i as i16).unwrap();
+        }
+        writer.finalize().unwrap();
+
+        wav.into_inner()
+    }
+
+    #[test]
+    fn detects_formats_from_their_magic_bytes() {
+        assert_eq!(MediaFormat::from_content(b"ID3\x04\x00\x00"), Some(MediaFormat::Mp3));
+        assert_eq!(MediaFormat::from_content(&[0xFF, 0xFB, 0x90, 0x64]), Some(MediaFormat::Mp3));
+        assert_eq!(MediaFormat::from_content(&[0xFF, 0xF1, 0x50, 0x80]), Some(MediaFormat::Aac));
+        assert_eq!(MediaFormat::from_content(b"fLaC\x00\x00\x00\x22"), Some(MediaFormat::Flac));
+        assert_eq!(MediaFormat::from_content(b"RIFF\x24\x08\x00\x00WAVEfmt "), Some(MediaFormat::Wav));
+        assert_eq!(MediaFormat::from_content(b"\x00\x00\x00\x20ftypM4A "), Some(MediaFormat::M4a));
+        assert_eq!(
+            MediaFormat::from_content(b"OggS\x00\x02\x00\x00\x00\x00\x00\x00\x00\x00\x01vorbis"),
+            Some(MediaFormat::Vorbis)
+        );
+        assert_eq!(
+            MediaFormat::from_content(b"OggS\x00\x02\x00\x00\x00\x00\x00\x00\x00\x00OpusHead"),
+            Some(MediaFormat::Opus)
+        );
+        assert_eq!(MediaFormat::from_content(&wav(8_000, 10)), Some(MediaFormat::Wav));
+    }
+
+    #[test]
+    fn rejects_unknown_content() {
+        assert_eq!(MediaFormat::from_content(b"OggS\x00\x02\x00\x00unknown"), None);
+        assert_eq!(MediaFormat::from_content(b"RIFF\x24\x08\x00\x00AVI "), None);
+        assert_eq!(MediaFormat::from_content(b"plain text"), None);
+        assert_eq!(MediaFormat::from_content(b""), None);
+    }
+
+    #[test]
+    fn falls_back_on_the_extension() {
+        let dir = std::env::temp_dir().join(format!("tuno-media-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let flac = dir.join("song.FLAC");
+        fs::write(&flac, b"no magic bytes here").unwrap();
+        assert_eq!(MediaFormat::detect(&flac).unwrap(), MediaFormat::Flac);
+
+        let text = dir.join("notes.txt");
+        fs::write(&text, b"no magic bytes here").unwrap();
+        assert!(matches!(MediaFormat::detect(&text), Err(MediaError::UnsupportedFormat(_))));
+
+        // Content wins over a misleading extension
+        let wav_named_mp3 = dir.join("song.mp3");
+        fs::write(&wav_named_mp3, wav(8_000, 10)).unwrap();
+        assert_eq!(MediaFormat::detect(&wav_named_mp3).unwrap(), MediaFormat::Wav);
+
+        fs::remove_dir_all(&dir).unwrap();
+        assert!(matches!(MediaFormat::detect(&flac), Err(MediaError::NotFound(_))));
+    }
+
+    #[test]
+    fn computes_duration_from_packets_without_frame_count() {
+        let probe = |bytes: Vec<u8>| get_probe().format(
+            &MediaFormat::Wav.hint(),
+            MediaSourceStream::new(Box::new(Cursor::new(bytes)), Default::default()),
+            &FormatOptions::default(),
+            &Default::default()
+        ).unwrap();
+
+        let song = wav(8_000, 12_000);
+        let mut probed = probe(song.clone());
+        let track = probed.format.default_track().unwrap();
+        let (track_id, params) = (track.id, track.codec_params.clone());
+        assert_eq!(compute_duration(Path::new("song.wav"), probed.format.as_mut(), track_id, &params).unwrap(), 1_500);
+
+        let mut probed = probe(song);
+        let mut params = params;
+        params.n_frames = None;
+        assert_eq!(compute_duration(Path::new("song.wav"), probed.format.as_mut(), track_id, &params).unwrap(), 1_500);
+    }
+}
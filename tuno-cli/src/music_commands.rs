@@ -1,38 +1,84 @@
+use std::collections::HashMap;
+use std::fs;
 use std::path::PathBuf;
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use clap::Parser;
 use iota_sdk::types::base_types::ObjectID;
 
 use crate::{
-    client::{Client, Connection, OwnedKiosk, SongMetadata},
-    local_storage::{store_song_from_file, FileMetadata}
+    client::{Client, Connection, KioskArgs, Pagination, SongMetadataArgs, SongUpdateArgs},
+    constants::DEFAULT_PROFILE,
+    displays::{CreatorRegistration, Listing, PublishReport, PublishedSong, SongDetails, SongTransaction, TabledPublishedTrack},
+    local_storage::{check_local_song, read_tags, store_song_from_file, FileMetadata},
+    manifest::{read_directory, read_manifest, TrackDefaults},
+    media::MediaInfo,
+    output::{print_output, OutputFormat},
+    profiles::{Profile, Profiles},
+    utils::confirm
 };
 
 #[derive(Parser)]
 pub enum MusicCommands {
-    /// Register active address as creator
+    /// Register active address as creator, saving its setup as a profile
     Register {
+        /// Name of the profile to save
+        #[arg(long, default_value = DEFAULT_PROFILE)]
+        save_as: String,
+
         #[command(flatten)]
         conn: Connection
     },
 
     /// Publish new song and make it available
     Publish {
-        /// MP3 file containing the song
+        /// Audio file containing the song (MP3, FLAC, Ogg Vorbis/Opus, AAC/M4A or WAV)
         #[arg(long)]
         file: PathBuf,
 
-        /// Creator capability's object id (created at registration)
+        /// Creator capability's object id (default: the profile's, or the only one owned)
         #[arg(long, env = "CREATOR_CAP")]
-        cap: ObjectID,
+        cap: Option<ObjectID>,
 
-        // fix: flatten makes arguments required. See: https://github.com/clap-rs/clap/issues/5092
+        /// Kiosk in which to make the song available, if one is given or owned
         #[command(flatten)]
-        owned_kiosk: Option<OwnedKiosk>,
+        kiosk_args: KioskArgs,
 
         #[command(flatten)]
-        song_md: SongMetadata,
+        song_md: SongMetadataArgs,
+
+        /// Publish without confirming the song's metadata
+        #[arg(long, short)]
+        yes: bool,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Publish every song of a directory or manifest and make them available
+    PublishBatch {
+        /// Directory containing the audio files to publish, titled after their tags or else their file name
+        #[arg(long, conflicts_with = "manifest", required_unless_present = "manifest")]
+        dir: Option<PathBuf>,
+
+        /// CSV, TOML or JSON manifest with each track's file and metadata
+        #[arg(long)]
+        manifest: Option<PathBuf>,
+
+        /// File in which to write a JSON report of the published songs
+        #[arg(long)]
+        report: Option<PathBuf>,
+
+        /// Creator capability's object id (default: the profile's, or the only one owned)
+        #[arg(long, env = "CREATOR_CAP")]
+        cap: Option<ObjectID>,
+
+        /// Kiosk in which to make the songs available, if one is given or owned
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        #[command(flatten)]
+        defaults: TrackDefaults,
         #[command(flatten)]
         conn: Connection
     },
@@ -44,7 +90,7 @@ pub enum MusicCommands {
         song: ObjectID,
 
         #[command(flatten)]
-        owned_kiosk: OwnedKiosk,
+        kiosk_args: KioskArgs,
 
         #[command(flatten)]
         conn: Connection
@@ -57,7 +103,7 @@ pub enum MusicCommands {
         song: ObjectID,
 
         #[command(flatten)]
-        owned_kiosk: OwnedKiosk,
+        kiosk_args: KioskArgs,
 
         #[command(flatten)]
         conn: Connection
@@ -65,114 +111,324 @@ pub enum MusicCommands {
 
     /// List all songs owned by the active address
     List {
+        #[command(flatten)]
+        pagination: Pagination,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Show the technical details of an audio file
+    Inspect {
+        /// Audio file to inspect
+        #[arg(long)]
+        file: PathBuf
+    },
+
+    /// Update song's metadata and refresh its display
+    SetSong {
+        /// Song's object id
+        #[arg(long)]
+        song: ObjectID,
+
+        #[command(flatten)]
+        changes: SongUpdateArgs,
+
+        /// Kiosk in which the song is available, to refresh its display (default: the one indexed for the song)
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        /// Update without confirming the new metadata
+        #[arg(long, short)]
+        yes: bool,
+
         #[command(flatten)]
         conn: Connection
     },
 
-    /// Update song's metadata
-    SetSong,
+    /// Show every detail of a song, and the state of its local copy
+    GetSong {
+        /// Song's object id
+        #[arg(long)]
+        song: ObjectID,
 
-    /// Get song's metadata
-    GetSong
+        #[command(flatten)]
+        conn: Connection
+    }
 }
 
 impl MusicCommands {
-    pub async fn execute(self) -> Result<()> {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
         match self {
             MusicCommands::Register {
+                save_as,
                 conn
             } => {
-                let client = Client::new(conn)?;
+                let client = Client::connect(conn).await?;
+                let mut profiles = Profiles::load(&client.network)?;
+                if profiles.contains(&save_as) {
+                    bail!("Profile {save_as} already exists on {}, choose another name with --save-as", client.network);
+                }
+
                 let (
                     creator,
                     digest
                 ) = client.register_creator().await?;
 
-                println!("Creator succesfully registered on {}", digest);
-                println!("CreatorCap: {}", creator.cap);
-                println!("Kiosk: {}", creator.kiosk);
-                println!("KioskOwnerCap: {}", creator.kiosk_cap);
-                
+                // The creator is registered, its setup is printed even if the profile cannot be saved
+                print_output(&CreatorRegistration { digest, creator: creator.clone() }, output)?;
+
+                profiles.insert(save_as.clone(), Profile { address: client.address, setup: creator });
+                match profiles.save() {
+                    Ok(location) => eprintln!("Saved as profile {save_as} in {}", location.display()),
+                    Err(e) => eprintln!("Warning: profile {save_as} not saved, {e:#}")
+                }
+
                 Ok(())
             }
 
             MusicCommands::Publish {
                 file,
-                owned_kiosk,
+                kiosk_args,
                 cap,
                 song_md,
+                yes,
                 conn
             } => {
-                let file_md = FileMetadata::from(&file);
-                let client = Client::new(conn)?;
+                let file_md = FileMetadata::try_from(&file)?;
+                let song_md = song_md.resolve(read_tags(&file)?)?;
+                eprintln!("{}", song_md);
+                eprintln!("Format: {}", file_md.format);
+
+                if !yes && !confirm("Publish this song?")? {
+                    eprintln!("Publication cancelled");
+                    return Ok(());
+                }
+
+                let client = Client::connect(conn).await?;
+                let cap = client.resolve_creator_cap(cap).await?;
+                let owned_kiosk = client.find_kiosk(&kiosk_args).await?;
 
                 let (
                     song,
                     digest
                 ) = client.create_song(cap, song_md, file_md).await?;
 
-                println!("Song succesfully published [{}]", digest);
-                println!("ID: {}", song);
+                let (available, unavailable) = match owned_kiosk {
+                    Some(owned_kiosk) => match client.make_song_available(song, owned_kiosk).await {
+                        Ok(digest) => (Some(digest), None),
+                        Err(e) => (None, Some(e.to_string()))
+                    },
+                    None => (None, None)
+                };
 
-                if let Some(owned_kiosk) = owned_kiosk {
-                    match client.make_song_available(song, owned_kiosk).await {
-                        Ok(digest) => println!("Status: + [{}]", digest),
-                        Err(e) => println!("Status: - [{e}]")
-                    }
-                } else {
-                    println!("Status: -");
+                let location = store_song_from_file(&file, &song.to_hex())?;
+
+                print_output(&PublishedSong { id: song, digest, available, unavailable, location }, output)
+            }
+
+            MusicCommands::PublishBatch {
+                dir,
+                manifest,
+                report,
+                cap,
+                kiosk_args,
+                defaults,
+                conn
+            } => {
+                let tracks = match (dir, manifest) {
+                    (_, Some(manifest)) => read_manifest(&manifest)?,
+                    (Some(dir), None) => read_directory(&dir)?,
+                    (None, None) => bail!("Either --dir or --manifest is required")
+                };
+
+                let (files, song_mds): (Vec<_>, Vec<_>) = tracks.into_iter()
+                    .map(|t| {
+                        let tags = read_tags(&t.file)?;
+                        t.resolve(&defaults, tags)
+                    })
+                    .collect::<Result<Vec<_>>>()?
+                    .into_iter()
+                    .unzip();
+
+                let handles: Vec<_> = files.iter()
+                    .cloned()
+                    .map(|file| tokio::task::spawn_blocking(move || FileMetadata::try_from(&file)))
+                    .collect();
+
+                let mut file_mds = vec![];
+                for handle in handles {
+                    file_mds.push(handle.await??);
+                }
+
+                let titles: Vec<_> = song_mds.iter().map(|s| s.title.clone()).collect();
+                let mime_types: Vec<_> = file_mds.iter().map(|f| f.format.mime_type()).collect();
+                let client = Client::connect(conn).await?;
+                let cap = client.resolve_creator_cap(cap).await?;
+                let owned_kiosk = client.find_kiosk(&kiosk_args).await?;
+                let created = client.create_songs(
+                    cap,
+                    song_mds.into_iter().zip(file_mds).collect()
+                ).await?;
+
+                let published: Vec<_> = created.iter()
+                    .filter_map(|c| c.as_ref().ok().map(|(song, _)| *song))
+                    .collect();
+
+                let availability: HashMap<_, _> = match &owned_kiosk {
+                    Some(owned_kiosk) => client.make_songs_available(&published, owned_kiosk).await
+                        .into_iter()
+                        .collect(),
+                    None => HashMap::new()
+                };
+
+                let mut tracks = vec![];
+                for (((file, title), mime_type), created) in files.iter()
+                    .zip(titles)
+                    .zip(mime_types)
+                    .zip(created)
+                {
+                    let track = match created {
+                        Ok((song, digest)) => {
+                            let (available, mut status) = match availability.get(&song) {
+                                Some(Ok(digest)) => (true, format!("available [{digest}]")),
+                                Some(Err(e)) => (false, format!("created [{digest}], unavailable: {e}")),
+                                None => (false, format!("created [{digest}]"))
+                            };
+
+                            // The song exists on chain by now, the report must still list it
+                            if let Err(e) = store_song_from_file(file, &song.to_hex()) {
+                                status.push_str(&format!(", not stored locally: {e}"));
+                            }
+
+                            TabledPublishedTrack {
+                                file: file.display().to_string(),
+                                title,
+                                mime_type: mime_type.to_string(),
+                                song_id: song.to_string(),
+                                available,
+                                status
+                            }
+                        },
+                        Err(e) => TabledPublishedTrack {
+                            file: file.display().to_string(),
+                            title,
+                            mime_type: mime_type.to_string(),
+                            song_id: "-".to_string(),
+                            available: false,
+                            status: e.to_string()
+                        }
+                    };
+
+                    tracks.push(track);
+                }
+
+                let summary = PublishReport(tracks);
+                print_output(&summary, output)?;
+
+                if let Some(report) = report {
+                    fs::write(&report, serde_json::to_string_pretty(&summary)?)?;
+                    eprintln!("Report written to {}", report.display());
                 }
 
-                println!("location: {}", store_song_from_file(&file, &song.to_hex())?.display());
-                
                 Ok(())
             }
 
             MusicCommands::MakeAvailable {
                 song,
-                owned_kiosk,
+                kiosk_args,
                 conn
             } => {
-                let client = Client::new(conn)?;
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
                 let digest = client.make_song_available(song, owned_kiosk).await?;
 
-                println!("Song ({}) is now available [{}]", song, digest);
-                Ok(())
+                print_output(&SongTransaction { song, digest, action: "is now available" }, output)
             }
 
             MusicCommands::MakeUnavailable {
                 song,
-                owned_kiosk,
+                kiosk_args,
                 conn
             } => {
-                let client = Client::new(conn)?;
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = client.resolve_kiosk(&kiosk_args).await?;
                 let digest = client.make_song_unavailable(song, owned_kiosk).await?;
 
-                println!("Song ({}) is now unavailable [{}]", song, digest);
-                Ok(())
+                print_output(&SongTransaction { song, digest, action: "is now unavailable" }, output)
             }
 
             MusicCommands::List {
+                pagination,
                 conn
             } => {
-                let client = Client::new(conn)?;
-                let songs = client.get_all_owned_songs().await?;
-
-                println!("{}", songs);
+                let client = Client::connect(conn).await?;
+                let (
+                    songs,
+                    skipped,
+                    next_cursor
+                ) = client.get_all_owned_songs(&pagination).await?;
+
+                print_output(&Listing {
+                    items: songs,
+                    skipped: skipped.iter().map(ToString::to_string).collect(),
+                    next_cursor
+                }, output)
+            }
 
-                Ok(())
+            MusicCommands::Inspect {
+                file
+            } => {
+                print_output(&MediaInfo::probe(&file)?, output)
             }
 
             MusicCommands::SetSong {
-
+                song,
+                changes,
+                kiosk_args,
+                yes,
+                conn
             } => {
-                todo!("set-song command");
+                let client = Client::connect(conn).await?;
+                let current = client.get_song(song).await?;
+                let song_md = changes.apply(&current)?;
+                eprintln!("{}", song_md);
+
+                let owned_kiosk = match current.display_id {
+                    Some(_) => client.find_song_kiosk(song, &kiosk_args).await?,
+                    None => None
+                };
+                if current.display_id.is_some() && owned_kiosk.is_none() {
+                    eprintln!("Song is available in no kiosk of the active address, its display will not be refreshed");
+                }
+
+                if !yes && !confirm("Update this song?")? {
+                    eprintln!("Update cancelled");
+                    return Ok(());
+                }
+
+                let digest = client.update_song(song, &song_md).await?;
+
+                // The metadata is updated even if the display cannot be refreshed
+                if let Some(owned_kiosk) = owned_kiosk {
+                    match client.refresh_song_display(song, &owned_kiosk).await {
+                        Ok(refresh) => eprintln!("Display refreshed in kiosk {} ({refresh})", owned_kiosk.kiosk),
+                        Err(e) => eprintln!("Display not refreshed in kiosk {}: {e:#}", owned_kiosk.kiosk)
+                    }
+                }
+
+                print_output(&SongTransaction { song, digest, action: "updated" }, output)
             }
 
             MusicCommands::GetSong {
-
+                song,
+                conn
             } => {
-                todo!("get-song command");
+                let client = Client::connect(conn).await?;
+                let song = client.get_song(song).await?;
+                let local_copy = check_local_song(&song.id.to_hex(), &song.signature)?;
+
+                print_output(&SongDetails::new(&song, local_copy), output)
             }
         }
     }
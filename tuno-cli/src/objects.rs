@@ -0,0 +1,300 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use thiserror::Error;
+
+use iota_sdk::rpc_types::{IotaObjectResponse, IotaRawData};
+use iota_sdk::types::balance::Balance;
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use iota_sdk::types::collection_types::VecMap;
+use iota_sdk::types::id::{ID, UID};
+use iota_sdk::types::{Identifier, TypeTag, IOTA_FRAMEWORK_PACKAGE_ID};
+use move_core_types::language_storage::StructTag;
+
+use crate::constants::{KIOSK_MODULE, TUNO_MODULE};
+use crate::utils::get_usdc_type_tag;
+
+/// Why an on-chain object could not be read as one of the package's types
+#[derive(Debug, Error)]
+pub enum ParseError {
+    #[error("object has no content ({0})")]
+    NoContent(String),
+    #[error("object {0} is not a Move object")]
+    NotMoveObject(ObjectID),
+    #[error("object {id} is a {found}, expected {expected}")]
+    UnexpectedType { id: ObjectID, found: String, expected: String },
+    #[error("object {id} does not match the layout of {name}: {error}")]
+    Layout { id: ObjectID, name: &'static str, error: bcs::Error },
+}
+
+/// Rust mirror of a Move struct, decoded from the object's BCS bytes.
+/// Fields must keep the order of the Move declaration, see `move/tests/bcs_tests.move`
+pub(crate) trait MoveObject: DeserializeOwned {
+    const MODULE: &'static str = TUNO_MODULE;
+    const NAME: &'static str;
+
+    /// Package declaring the struct, given the client's package
+    fn address(package: ObjectID) -> ObjectID {
+        package
+    }
+
+    /// Type arguments with which the client instantiates the struct
+    fn type_params() -> Vec<TypeTag> {
+        vec![]
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MoveSong {
+    pub id: UID,
+    pub title: String,
+    pub artist: String,
+    pub album: String,
+    pub release_year: u64,
+    pub genre: String,
+    pub cover_art_url: String,
+    pub streaming_price: u64,
+    pub owner: IotaAddress,
+    pub length: u64,
+    pub duration: u64,
+    pub signature: Vec<Vec<u8>>,
+    pub creator_balance: Balance,
+    pub distributors: VecMap<IotaAddress, MoveDistributor>,
+    pub display_id: Option<ID>,
+}
+
+impl MoveObject for MoveSong {
+    const NAME: &'static str = "Song";
+
+    fn type_params() -> Vec<TypeTag> {
+        vec![get_usdc_type_tag().unwrap()]
+    }
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MoveDistributor {
+    pub url: String,
+    pub joined_at: u64,
+    pub streaming_price: u64,
+    pub balance: Balance,
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MoveCreatorCap {
+    pub id: UID,
+    pub creator: IotaAddress,
+}
+
+impl MoveObject for MoveCreatorCap {
+    const NAME: &'static str = "CreatorCap";
+}
+
+#[derive(Deserialize)]
+pub(crate) struct MoveSongDisplay {
+    pub id: UID,
+    pub song_id: ID,
+    pub title: String,
+    pub artist: String,
+    pub genre: String,
+    pub streaming_price: u64,
+    pub cover_art_url: String,
+}
+
+impl MoveObject for MoveSongDisplay {
+    const NAME: &'static str = "SongDisplay";
+
+    fn type_params() -> Vec<TypeTag> {
+        vec![get_usdc_type_tag().unwrap()]
+    }
+}
+
+/// Kiosk of the framework, see `iota::kiosk`
+#[derive(Deserialize)]
+pub(crate) struct MoveKiosk {
+    pub id: UID,
+    pub profits: Balance,
+    pub owner: IotaAddress,
+    pub item_count: u32,
+    pub allow_extensions: bool,
+}
+
+impl MoveObject for MoveKiosk {
+    const MODULE: &'static str = KIOSK_MODULE;
+    const NAME: &'static str = "Kiosk";
+
+    fn address(_: ObjectID) -> ObjectID {
+        IOTA_FRAMEWORK_PACKAGE_ID
+    }
+}
+
+/// Capability over a kiosk of the framework, see `iota::kiosk`
+#[derive(Deserialize)]
+pub(crate) struct MoveKioskOwnerCap {
+    pub id: UID,
+    pub kiosk: ID,
+}
+
+impl MoveObject for MoveKioskOwnerCap {
+    const MODULE: &'static str = KIOSK_MODULE;
+    const NAME: &'static str = "KioskOwnerCap";
+
+    fn address(_: ObjectID) -> ObjectID {
+        IOTA_FRAMEWORK_PACKAGE_ID
+    }
+}
+
+/// Type of a mirrored struct, as instantiated by the client of `package`
+pub(crate) fn struct_tag<M: MoveObject>(package: ObjectID) -> StructTag {
+    StructTag {
+        address: M::address(package).into(),
+        module: Identifier::new(M::MODULE).unwrap(),
+        name: Identifier::new(M::NAME).unwrap(),
+        type_params: M::type_params(),
+    }
+}
+
+/// Decodes an object response, fetched with its BCS bytes, as one of the mirrored structs
+/// of `package`. Objects of the same name declared by another package are rejected
+pub(crate) fn parse_object<M: MoveObject>(obj: IotaObjectResponse, package: ObjectID) -> Result<M, ParseError> {
+    let Some(data) = obj.data else {
+        return Err(ParseError::NoContent(
+            obj.error.map_or("unknown error".to_string(), |e| e.to_string())
+        ));
+    };
+
+    let raw = match data.bcs {
+        Some(IotaRawData::MoveObject(raw)) => raw,
+        Some(_) => return Err(ParseError::NotMoveObject(data.object_id)),
+        None => return Err(ParseError::NoContent(data.object_id.to_string()))
+    };
+
+    let expected = struct_tag::<M>(package);
+    if raw.type_ != expected {
+        return Err(ParseError::UnexpectedType {
+            id: data.object_id,
+            found: raw.type_.to_string(),
+            expected: expected.to_string()
+        });
+    }
+
+    decode(data.object_id, &raw.bcs_bytes)
+}
+
+fn decode<M: MoveObject>(id: ObjectID, bytes: &[u8]) -> Result<M, ParseError> {
+    bcs::from_bytes(bytes).map_err(|error| ParseError::Layout { id, name: M::NAME, error })
+}
+
+/// Decodes every object response, keeping apart the ones that could not be decoded
+pub(crate) fn parse_objects<M, T>(objects: Vec<IotaObjectResponse>, package: ObjectID) -> (Vec<T>, Vec<ParseError>)
+where
+    M: MoveObject,
+    T: From<M>
+{
+    let mut parsed = vec![];
+    let mut skipped = vec![];
+    for obj in objects {
+        match parse_object::<M>(obj, package) {
+            Ok(m) => parsed.push(T::from(m)),
+            Err(e) => skipped.push(e)
+        }
+    }
+
+    (parsed, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+    use iota_sdk::types::IOTA_FRAMEWORK_PACKAGE_ID;
+
+    use super::*;
+
+    // Golden bytes of `move/tests/bcs_tests.move`, without the leading object ids
+    const SONG_BYTES: &str = "095465737420536f6e670b54657374204172746973740a5465737420416c62756de9070000000000000a456c656374726f6e69631c687474703a2f2f6578616d706c652e636f6d2f636f7665722e6a7067809698000000000000000000000000000000000000000000000000000000000000000000000000c100000001000000005a0000000000000002030102030304050600000000000000000000";
+    const CREATOR_CAP_BYTES: &str = "00000000000000000000000000000000000000000000000000000000000000c1";
+    const SONG_DISPLAY_BYTES: &str = "095465737420536f6e670b54657374204172746973740a456c656374726f6e696380969800000000001c687474703a2f2f6578616d706c652e636f6d2f636f7665722e6a7067";
+
+    const OBJECT_ID: [u8; 32] = [0x11; 32];
+    const SONG_ID: [u8; 32] = [0x22; 32];
+
+    fn golden(ids: &[[u8; 32]], bytes: &str) -> Vec<u8> {
+        let mut golden = ids.concat();
+        golden.extend(hex::decode(bytes).unwrap());
+        golden
+    }
+
+    fn creator() -> IotaAddress {
+        IotaAddress::from_bytes(hex::decode(CREATOR_CAP_BYTES).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn decodes_song() {
+        let song: MoveSong = decode(ObjectID::ZERO, &golden(&[OBJECT_ID], SONG_BYTES)).unwrap();
+
+        assert_eq!(*song.id.object_id(), ObjectID::new(OBJECT_ID));
+        assert_eq!(song.title, "Test Song");
+        assert_eq!(song.artist, "Test Artist");
+        assert_eq!(song.album, "Test Album");
+        assert_eq!(song.release_year, 2025);
+        assert_eq!(song.genre, "Electronic");
+        assert_eq!(song.cover_art_url, "http://example.com/cover.jpg");
+        assert_eq!(song.streaming_price, 10_000_000);
+        assert_eq!(song.owner, creator());
+        assert_eq!(song.length, 16_777_216);
+        assert_eq!(song.duration, 90);
+        assert_eq!(song.signature, vec![vec![1, 2, 3], vec![4, 5, 6]]);
+        assert_eq!(song.creator_balance.value(), 0);
+        assert!(song.distributors.contents.is_empty());
+        assert!(song.display_id.is_none());
+    }
+
+    #[test]
+    fn decodes_creator_cap() {
+        let cap: MoveCreatorCap = decode(ObjectID::ZERO, &golden(&[OBJECT_ID], CREATOR_CAP_BYTES)).unwrap();
+
+        assert_eq!(*cap.id.object_id(), ObjectID::new(OBJECT_ID));
+        assert_eq!(cap.creator, creator());
+    }
+
+    #[test]
+    fn decodes_song_display() {
+        let display: MoveSongDisplay = decode(
+            ObjectID::ZERO,
+            &golden(&[OBJECT_ID, SONG_ID], SONG_DISPLAY_BYTES)
+        ).unwrap();
+
+        assert_eq!(*display.id.object_id(), ObjectID::new(OBJECT_ID));
+        assert_eq!(display.song_id.bytes, ObjectID::new(SONG_ID));
+        assert_eq!(display.title, "Test Song");
+        assert_eq!(display.artist, "Test Artist");
+        assert_eq!(display.genre, "Electronic");
+        assert_eq!(display.streaming_price, 10_000_000);
+        assert_eq!(display.cover_art_url, "http://example.com/cover.jpg");
+    }
+
+    #[test]
+    fn rejects_truncated_bytes() {
+        let mut bytes = golden(&[OBJECT_ID], SONG_BYTES);
+        bytes.truncate(bytes.len() - 2);
+
+        assert!(matches!(
+            decode::<MoveSong>(ObjectID::ZERO, &bytes),
+            Err(ParseError::Layout { name: "Song", .. })
+        ));
+    }
+
+    #[test]
+    fn struct_tags_are_declared_by_their_package() {
+        let package = ObjectID::new([0x33; 32]);
+
+        let song = struct_tag::<MoveSong>(package);
+        assert_eq!(ObjectID::from(song.address), package);
+        assert_eq!(song.type_params, vec![get_usdc_type_tag().unwrap()]);
+
+        let kiosk = struct_tag::<MoveKiosk>(package);
+        assert_eq!(ObjectID::from(kiosk.address), IOTA_FRAMEWORK_PACKAGE_ID);
+        assert!(kiosk.type_params.is_empty());
+
+        // Same module and name, declared by another package
+        assert_ne!(struct_tag::<MoveSong>(ObjectID::new([0x44; 32])), song);
+    }
+}
@@ -0,0 +1,81 @@
+use std::fmt::Display;
+use std::io;
+
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use serde_json::Value;
+
+/// Format in which to print a command's result
+#[derive(ValueEnum, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Tables for humans
+    #[default]
+    Table,
+    /// A single pretty-printed JSON document
+    Json,
+    /// One JSON object per line, one line per row
+    Ndjson,
+    /// One CSV record per row, nested values as JSON
+    Csv,
+    /// A single YAML document
+    Yaml,
+}
+
+/// Result of a command, printed as a table or serialized for scripts
+pub(crate) trait CommandOutput: Serialize + Display {
+    /// Rows written by the line-oriented formats (ndjson and csv)
+    fn rows(&self) -> Result<Vec<Value>> {
+        Ok(vec![serde_json::to_value(self)?])
+    }
+}
+
+/// Prints the result of a command on stdout in the given format
+pub(crate) fn print_output<T: CommandOutput>(result: &T, output: OutputFormat) -> Result<()> {
+    match output {
+        OutputFormat::Table => println!("{result}"),
+        OutputFormat::Json => println!("{}", serde_json::to_string_pretty(result)?),
+        OutputFormat::Yaml => print!("{}", serde_yaml::to_string(result)?),
+        OutputFormat::Ndjson => {
+            for row in result.rows()? {
+                println!("{}", serde_json::to_string(&row)?);
+            }
+        },
+        OutputFormat::Csv => write_csv(result.rows()?)?,
+    }
+
+    Ok(())
+}
+
+/// Writes rows as CSV, with the fields of the first row as header
+fn write_csv(rows: Vec<Value>) -> Result<()> {
+    let mut writer = csv::Writer::from_writer(io::stdout());
+
+    let header: Vec<String> = match rows.first() {
+        Some(Value::Object(fields)) => fields.keys().cloned().collect(),
+        Some(_) => vec!["value".to_string()],
+        None => return Ok(())
+    };
+    writer.write_record(&header)?;
+
+    for row in rows {
+        let record: Vec<String> = match row {
+            Value::Object(fields) => header.iter()
+                .map(|key| fields.get(key).map_or_else(String::new, csv_cell))
+                .collect(),
+            value => vec![csv_cell(&value)]
+        };
+        writer.write_record(record)?;
+    }
+
+    writer.flush()?;
+    Ok(())
+}
+
+fn csv_cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string()
+    }
+}
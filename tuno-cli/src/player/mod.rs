@@ -0,0 +1,286 @@
+mod sink;
+mod source;
+
+use std::io::{self, BufRead as _, ErrorKind};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, Sender};
+use std::thread;
+
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+
+use anyhow::{bail, Context as _, Result};
+use log::warn;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_OPUS};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+use symphonia::core::units::Time;
+use symphonia::default::{get_codecs, get_probe};
+
+pub(crate) use sink::{AudioSink, WavSink};
+#[cfg(feature = "cpal")]
+pub(crate) use sink::DeviceSink;
+pub(crate) use source::{StreamBuffer, StreamReader};
+
+use crate::media::MediaError;
+
+/// Default step of relative seeks, in seconds
+const SEEK_STEP: f64 = 10.0;
+
+/// Commands read from stdin while a song plays
+#[derive(Debug, PartialEq)]
+pub(crate) enum Control {
+    TogglePause,
+    /// Seek by the given number of seconds from the current position
+    SeekBy(f64),
+    /// Seek to the given number of seconds from the start
+    SeekTo(f64),
+    Stop,
+}
+
+impl Control {
+    pub(crate) const HELP: &'static str =
+        "Controls: p [enter] pause/resume, +[secs] / -[secs] seek, <secs> jump to, q quit";
+
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        match line {
+            "p" => Some(Self::TogglePause),
+            "q" => Some(Self::Stop),
+            "+" => Some(Self::SeekBy(SEEK_STEP)),
+            "-" => Some(Self::SeekBy(-SEEK_STEP)),
+            _ if line.starts_with(['+', '-']) => line.parse().ok().map(Self::SeekBy),
+            _ => line.parse().ok().map(Self::SeekTo),
+        }
+    }
+}
+
+/// Forwards the controls typed on stdin until it is closed
+pub(crate) fn read_controls(controls: Sender<Control>) {
+    thread::spawn(move || {
+        for line in io::stdin().lock().lines() {
+            let Ok(line) = line else { break };
+            match Control::parse(&line) {
+                Some(control) => if controls.send(control).is_err() { break },
+                None => eprintln!("{}", Control::HELP)
+            }
+        }
+    });
+}
+
+/// Where to output a song
+pub(crate) enum SinkTarget {
+    Wav(PathBuf),
+    Device,
+}
+
+impl SinkTarget {
+    fn open(self) -> Result<Box<dyn AudioSink>> {
+        match self {
+            Self::Wav(path) => Ok(Box::new(WavSink::create(&path)?)),
+            #[cfg(feature = "cpal")]
+            Self::Device => Ok(Box::new(DeviceSink::new()?)),
+            #[cfg(not(feature = "cpal"))]
+            Self::Device => bail!("Built without audio device support, use --wav or build with `--features cpal`"),
+        }
+    }
+}
+
+/// Opens the sink on a thread of its own, as audio streams may not be `Send`, and plays the song
+/// there. Returns once the sink is open, so that a song is never paid for without a way to output it
+pub(crate) async fn start(
+    source: StreamReader,
+    target: SinkTarget,
+    controls: Receiver<Control>
+) -> Result<JoinHandle<Result<()>>> {
+    let (opened_tx, opened_rx) = oneshot::channel();
+    let playing = tokio::task::spawn_blocking(move || {
+        let mut sink = match target.open() {
+            Ok(sink) => sink,
+            Err(e) => {
+                let _ = opened_tx.send(Err(e));
+                return Ok(());
+            }
+        };
+
+        let _ = opened_tx.send(Ok(()));
+        play(source, sink.as_mut(), controls)
+    });
+
+    opened_rx.await??;
+    Ok(playing)
+}
+
+/// Decodes a song as it is received and outputs it to the sink, following the controls
+fn play(source: StreamReader, sink: &mut dyn AudioSink, controls: Receiver<Control>) -> Result<()> {
+    let probed = get_probe().format(
+        &Hint::new(),
+        MediaSourceStream::new(Box::new(source), Default::default()),
+        &FormatOptions { enable_gapless: true, ..Default::default() },
+        &MetadataOptions::default()
+    ).context("Unsupported or corrupted stream")?;
+    let mut format = probed.format;
+
+    let Some(track) = format.default_track() else {
+        bail!("Stream has no audio track");
+    };
+    // Opus songs are published and distributed, but Symphonia cannot decode them
+    if track.codec_params.codec == CODEC_TYPE_OPUS {
+        return Err(MediaError::UnsupportedCodec("Opus").into());
+    }
+
+    let track_id = track.id;
+    let time_base = track.codec_params.time_base;
+    let mut decoder = get_codecs().make(&track.codec_params, &DecoderOptions::default())?;
+
+    let mut position = 0.0;
+    let mut paused = false;
+    loop {
+        let control = match paused {
+            // Nothing to do until resumed, or until stdin is closed
+            true => Some(controls.recv().unwrap_or(Control::TogglePause)),
+            false => controls.try_recv().ok()
+        };
+
+        match control {
+            Some(Control::Stop) => break,
+            Some(Control::TogglePause) => {
+                paused = !paused;
+                match paused {
+                    true => sink.pause()?,
+                    false => sink.resume()?
+                }
+                eprintln!("{} at {}", if paused { "Paused" } else { "Resumed" }, timestamp(position));
+                continue;
+            },
+            Some(Control::SeekBy(seconds)) => {
+                seek(format.as_mut(), decoder.as_mut(), sink, track_id, position + seconds);
+            },
+            Some(Control::SeekTo(seconds)) => {
+                seek(format.as_mut(), decoder.as_mut(), sink, track_id, seconds);
+            },
+            None => {}
+        }
+
+        let packet = match format.next_packet() {
+            Ok(packet) => packet,
+            Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => break,
+            Err(e) => return Err(e).context("Failed to read the stream"),
+        };
+        if packet.track_id() != track_id {
+            continue;
+        }
+
+        if let Some(time_base) = time_base {
+            let time = time_base.calc_time(packet.ts());
+            position = time.seconds as f64 + time.frac;
+        }
+
+        let decoded = match decoder.decode(&packet) {
+            Ok(decoded) => decoded,
+            Err(SymphoniaError::DecodeError(e)) => {
+                warn!("Skipped undecodable packet: {e}");
+                continue;
+            },
+            Err(e) => return Err(e).context("Failed to decode the stream"),
+        };
+
+        let spec = *decoded.spec();
+        let mut samples = SampleBuffer::<f32>::new(decoded.capacity() as u64, spec);
+        samples.copy_interleaved_ref(decoded);
+        sink.write(spec, samples.samples())?;
+    }
+
+    sink.finish()
+}
+
+/// Seeks accurately to a position, which waits for the stream to get there
+fn seek(
+    format: &mut dyn FormatReader,
+    decoder: &mut dyn Decoder,
+    sink: &mut dyn AudioSink,
+    track_id: u32,
+    seconds: f64
+) {
+    let seconds = seconds.max(0.0);
+    match format.seek(SeekMode::Accurate, SeekTo::Time { time: Time::from(seconds), track_id: Some(track_id) }) {
+        Ok(_) => {
+            decoder.reset();
+            sink.flush();
+            eprintln!("Seeked to {}", timestamp(seconds));
+        },
+        Err(e) => eprintln!("Cannot seek to {}: {e}", timestamp(seconds))
+    }
+}
+
+fn timestamp(seconds: f64) -> String {
+    let seconds = seconds as u64;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+    use std::sync::mpsc;
+
+    use hound::{SampleFormat, WavReader, WavSpec, WavWriter};
+
+    use super::*;
+
+    #[test]
+    fn parses_controls() {
+        assert_eq!(Control::parse("p"), Some(Control::TogglePause));
+        assert_eq!(Control::parse(" q \n"), Some(Control::Stop));
+        assert_eq!(Control::parse("+"), Some(Control::SeekBy(SEEK_STEP)));
+        assert_eq!(Control::parse("-"), Some(Control::SeekBy(-SEEK_STEP)));
+        assert_eq!(Control::parse("+30"), Some(Control::SeekBy(30.0)));
+        assert_eq!(Control::parse("-2.5"), Some(Control::SeekBy(-2.5)));
+        assert_eq!(Control::parse("75"), Some(Control::SeekTo(75.0)));
+        assert_eq!(Control::parse("pause"), None);
+        assert_eq!(Control::parse(""), None);
+    }
+
+    #[test]
+    fn plays_a_streamed_wav_into_a_wav_sink() {
+        let samples: Vec<i16> = (0..800).map(|i| (i * 40 - 16_000) as i16).collect();
+
+        let mut song = Cursor::new(vec![]);
+        let mut writer = WavWriter::new(&mut song, WavSpec {
+            channels: 1,
+            sample_rate: 8_000,
+            bits_per_sample: 16,
+            sample_format: SampleFormat::Int,
+        }).unwrap();
+        for sample in &samples {
+            writer.write_sample(*sample).unwrap();
+        }
+        writer.finalize().unwrap();
+        let song = song.into_inner();
+
+        let buffer = StreamBuffer::new(song.len());
+        for chunk in song.chunks(100) {
+            buffer.push(chunk);
+        }
+        buffer.close(None);
+
+        let mut output = vec![];
+        let mut sink = WavSink::new(Cursor::new(&mut output));
+        let (_controls, controls_rx) = mpsc::channel();
+        play(buffer.reader(), &mut sink, controls_rx).unwrap();
+        drop(sink);
+
+        let mut reader = WavReader::new(Cursor::new(output)).unwrap();
+        assert_eq!(reader.spec().channels, 1);
+        assert_eq!(reader.spec().sample_rate, 8_000);
+
+        let played: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+        assert_eq!(played.len(), samples.len());
+        for (played, sample) in played.iter().zip(&samples) {
+            assert!((played - *sample as f32 / 32_768.0).abs() < 1e-4);
+        }
+    }
+}
@@ -0,0 +1,187 @@
+use std::fs::File;
+use std::io::{BufWriter, Seek, Write};
+use std::path::Path;
+
+use anyhow::{Context as _, Result};
+use hound::{SampleFormat, WavSpec, WavWriter};
+use symphonia::core::audio::SignalSpec;
+
+/// Output of the decoded samples
+pub(crate) trait AudioSink {
+    /// Outputs interleaved samples, blocking while the sink is full
+    fn write(&mut self, spec: SignalSpec, samples: &[f32]) -> Result<()>;
+
+    fn pause(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    fn resume(&mut self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Drops the samples not output yet, after a seek
+    fn flush(&mut self) {}
+
+    /// Waits for every sample to be output
+    fn finish(&mut self) -> Result<()>;
+}
+
+/// Writes the samples to a WAV file, for listening without an audio device
+pub(crate) struct WavSink<W: Write + Seek = BufWriter<File>> {
+    output: Option<W>,
+    writer: Option<WavWriter<W>>,
+}
+
+impl WavSink {
+    /// Creates the file right away, so that an unwritable path fails before the song is streamed
+    pub(crate) fn create(path: &Path) -> Result<Self> {
+        let file = File::create(path).with_context(|| format!("Cannot create {}", path.display()))?;
+        Ok(Self::new(BufWriter::new(file)))
+    }
+}
+
+impl<W: Write + Seek> WavSink<W> {
+    pub(crate) fn new(output: W) -> Self {
+        Self { output: Some(output), writer: None }
+    }
+}
+
+impl<W: Write + Seek> AudioSink for WavSink<W> {
+    fn write(&mut self, spec: SignalSpec, samples: &[f32]) -> Result<()> {
+        if let Some(output) = self.output.take() {
+            self.writer = Some(WavWriter::new(output, WavSpec {
+                channels: spec.channels.count() as u16,
+                sample_rate: spec.rate,
+                bits_per_sample: 32,
+                sample_format: SampleFormat::Float,
+            })?);
+        }
+
+        let writer = self.writer.as_mut().unwrap();
+        for sample in samples {
+            writer.write_sample(*sample)?;
+        }
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<()> {
+        if let Some(writer) = self.writer.take() {
+            writer.finalize()?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(feature = "cpal")]
+pub(crate) use device::DeviceSink;
+
+#[cfg(feature = "cpal")]
+mod device {
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::thread;
+    use std::time::Duration;
+
+    use anyhow::{Context as _, Result};
+    use cpal::traits::{DeviceTrait as _, HostTrait as _, StreamTrait as _};
+    use cpal::{BufferSize, Device, SampleRate, Stream, StreamConfig};
+    use log::error;
+    use symphonia::core::audio::SignalSpec;
+
+    use super::AudioSink;
+
+    /// Plays the samples on the default output device
+    pub(crate) struct DeviceSink {
+        device: Device,
+        queue: Arc<Mutex<VecDeque<f32>>>,
+        stream: Option<Stream>,
+        /// Samples queued before `write` blocks, a second of audio
+        capacity: usize,
+    }
+
+    impl DeviceSink {
+        /// Finds the output device, its stream is opened with the first samples
+        pub(crate) fn new() -> Result<Self> {
+            let device = cpal::default_host()
+                .default_output_device()
+                .context("No audio output device")?;
+
+            Ok(Self { device, queue: Arc::default(), stream: None, capacity: 0 })
+        }
+
+        fn open(&mut self, spec: SignalSpec) -> Result<()> {
+            let config = StreamConfig {
+                channels: spec.channels.count() as u16,
+                sample_rate: SampleRate(spec.rate),
+                buffer_size: BufferSize::Default,
+            };
+
+            let queue = self.queue.clone();
+            let stream = self.device.build_output_stream(
+                &config,
+                move |out: &mut [f32], _| {
+                    let mut queue = queue.lock().unwrap();
+                    for sample in out {
+                        *sample = queue.pop_front().unwrap_or(0.0);
+                    }
+                },
+                |e| error!("Audio output error: {e}"),
+                None
+            )?;
+            stream.play()?;
+
+            self.capacity = spec.rate as usize * spec.channels.count();
+            self.stream = Some(stream);
+            Ok(())
+        }
+
+        fn queued(&self) -> usize {
+            self.queue.lock().unwrap().len()
+        }
+    }
+
+    impl AudioSink for DeviceSink {
+        fn write(&mut self, spec: SignalSpec, samples: &[f32]) -> Result<()> {
+            if self.stream.is_none() {
+                self.open(spec)?;
+            }
+
+            self.queue.lock().unwrap().extend(samples);
+            while self.queued() > self.capacity {
+                thread::sleep(Duration::from_millis(50));
+            }
+
+            Ok(())
+        }
+
+        fn pause(&mut self) -> Result<()> {
+            if let Some(stream) = &self.stream {
+                stream.pause()?;
+            }
+
+            Ok(())
+        }
+
+        fn resume(&mut self) -> Result<()> {
+            if let Some(stream) = &self.stream {
+                stream.play()?;
+            }
+
+            Ok(())
+        }
+
+        fn flush(&mut self) {
+            self.queue.lock().unwrap().clear();
+        }
+
+        fn finish(&mut self) -> Result<()> {
+            while self.stream.is_some() && self.queued() > 0 {
+                thread::sleep(Duration::from_millis(50));
+            }
+
+            Ok(())
+        }
+    }
+}
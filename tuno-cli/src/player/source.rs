@@ -0,0 +1,163 @@
+use std::io::{self, Read, Seek, SeekFrom};
+use std::sync::{Arc, Condvar, Mutex};
+
+use symphonia::core::io::MediaSource;
+
+#[derive(Default)]
+struct Received {
+    data: Vec<u8>,
+    closed: bool,
+    error: Option<String>,
+}
+
+/// Verified bytes of a song, growing while it is streamed from a distributor
+#[derive(Clone)]
+pub(crate) struct StreamBuffer {
+    received: Arc<(Mutex<Received>, Condvar)>,
+    length: u64,
+}
+
+impl StreamBuffer {
+    pub(crate) fn new(length: usize) -> Self {
+        Self {
+            received: Arc::new((Mutex::new(Received::default()), Condvar::new())),
+            length: length as u64,
+        }
+    }
+
+    pub(crate) fn push(&self, chunk: &[u8]) {
+        let (lock, available) = &*self.received;
+        lock.lock().unwrap().data.extend_from_slice(chunk);
+        available.notify_all();
+    }
+
+    /// Marks the end of the stream, failed when given an error
+    pub(crate) fn close(&self, error: Option<String>) {
+        let (lock, available) = &*self.received;
+        let mut received = lock.lock().unwrap();
+        received.closed = true;
+        received.error = error;
+        available.notify_all();
+    }
+
+    pub(crate) fn reader(&self) -> StreamReader {
+        StreamReader { buffer: self.clone(), position: 0 }
+    }
+}
+
+/// Reads a `StreamBuffer` as a file, waiting for the bytes that were not received yet
+pub(crate) struct StreamReader {
+    buffer: StreamBuffer,
+    position: u64,
+}
+
+impl Read for StreamReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (lock, available) = &*self.buffer.received;
+        let mut received = lock.lock().unwrap();
+        while self.position >= received.data.len() as u64 && !received.closed {
+            received = available.wait(received).unwrap();
+        }
+
+        let start = (self.position as usize).min(received.data.len());
+        let n = buf.len().min(received.data.len() - start);
+        if n == 0 {
+            if let Some(e) = &received.error {
+                return Err(io::Error::other(e.clone()));
+            }
+        }
+
+        buf[..n].copy_from_slice(&received.data[start..start + n]);
+        self.position += n as u64;
+        Ok(n)
+    }
+}
+
+impl Seek for StreamReader {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let position = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+            SeekFrom::End(offset) => self.buffer.length.checked_add_signed(offset),
+        };
+
+        self.position = position.ok_or_else(|| io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "invalid seek to a negative position"
+        ))?;
+        Ok(self.position)
+    }
+}
+
+impl MediaSource for StreamReader {
+    fn is_seekable(&self) -> bool {
+        true
+    }
+
+    fn byte_len(&self) -> Option<u64> {
+        Some(self.buffer.length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn reads_wait_for_pushed_data() {
+        let buffer = StreamBuffer::new(4);
+        let mut reader = buffer.reader();
+
+        let reading = thread::spawn(move || {
+            let mut data = [0; 4];
+            reader.read_exact(&mut data).map(|_| data)
+        });
+
+        thread::sleep(Duration::from_millis(50));
+        assert!(!reading.is_finished());
+
+        buffer.push(&[1, 2]);
+        buffer.push(&[3, 4]);
+        assert_eq!(reading.join().unwrap().unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn seeks_within_received_data() {
+        let buffer = StreamBuffer::new(6);
+        buffer.push(&[0, 1, 2, 3, 4, 5]);
+        buffer.close(None);
+        let mut reader = buffer.reader();
+
+        let mut data = [0; 2];
+        reader.seek(SeekFrom::Start(3)).unwrap();
+        reader.read_exact(&mut data).unwrap();
+        assert_eq!(data, [3, 4]);
+
+        reader.seek(SeekFrom::Current(-4)).unwrap();
+        reader.read_exact(&mut data).unwrap();
+        assert_eq!(data, [1, 2]);
+
+        assert_eq!(reader.seek(SeekFrom::End(-1)).unwrap(), 5);
+        assert_eq!(reader.read(&mut data).unwrap(), 1);
+        assert_eq!(reader.read(&mut data).unwrap(), 0);
+
+        assert!(reader.seek(SeekFrom::Current(-7)).is_err());
+    }
+
+    #[test]
+    fn failed_stream_is_a_read_error() {
+        let buffer = StreamBuffer::new(4);
+        buffer.push(&[1, 2]);
+        buffer.close(Some("distributor went away".to_string()));
+        let mut reader = buffer.reader();
+
+        let mut data = [0; 4];
+        assert_eq!(reader.read(&mut data).unwrap(), 2);
+
+        let error = reader.read(&mut data).unwrap_err();
+        assert_eq!(error.to_string(), "distributor went away");
+    }
+}
@@ -0,0 +1,82 @@
+use anyhow::{Context as _, Result};
+use clap::Parser;
+
+use crate::client::WalletArgs;
+use crate::displays::{ProfileDetails, ProfileList};
+use crate::output::{print_output, OutputFormat};
+use crate::profiles::Profiles;
+
+#[derive(Parser)]
+pub enum ProfileCommands {
+    /// List the profiles saved for the wallet's network
+    List {
+        #[command(flatten)]
+        wallet: WalletArgs
+    },
+
+    /// Make a profile the one used when `--profile` is not given
+    Use {
+        /// Profile's name
+        name: String,
+
+        #[command(flatten)]
+        wallet: WalletArgs
+    },
+
+    /// Show a profile's capabilities and kiosk
+    Show {
+        /// Profile's name (default: the active profile)
+        name: Option<String>,
+
+        #[command(flatten)]
+        wallet: WalletArgs
+    }
+}
+
+impl ProfileCommands {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
+        match self {
+            ProfileCommands::List {
+                wallet
+            } => {
+                let (_, network) = wallet.active()?;
+                let profiles = Profiles::load(&network)?;
+
+                print_output(&ProfileList::new(&profiles), output)
+            }
+
+            ProfileCommands::Use {
+                name,
+                wallet
+            } => {
+                let (address, network) = wallet.active()?;
+                let mut profiles = Profiles::load(&network)?;
+                profiles.set_active(&name)?;
+                profiles.save()?;
+
+                let profile = profiles.get(&name)?;
+                if profile.address != address {
+                    eprintln!("Profile {name} belongs to {}, it is only used once that address is active", profile.address);
+                }
+
+                print_output(&ProfileDetails::new(&name, &profiles)?, output)
+            }
+
+            ProfileCommands::Show {
+                name,
+                wallet
+            } => {
+                let (_, network) = wallet.active()?;
+                let profiles = Profiles::load(&network)?;
+                let name = match name {
+                    Some(name) => name,
+                    None => profiles.active()
+                        .map(|(name, _)| name.to_string())
+                        .with_context(|| format!("No active profile on {network}, see `profile use`"))?
+                };
+
+                print_output(&ProfileDetails::new(&name, &profiles)?, output)
+            }
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{anyhow, Context as _, Result};
+use serde::{Deserialize, Serialize};
+
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+
+use crate::client::CreatorSetup;
+use crate::constants::{PROFILES_DIR, PROFILES_STORAGE};
+
+/// Creator setup of an address, saved under a name
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Profile {
+    pub address: IotaAddress,
+    #[serde(flatten)]
+    pub setup: CreatorSetup,
+}
+
+/// Profiles saved for a network, with the one used when `--profile` is not given
+#[derive(Default, Serialize, Deserialize)]
+pub(crate) struct Profiles {
+    #[serde(skip)]
+    network: String,
+    active: Option<String>,
+    #[serde(default)]
+    profiles: BTreeMap<String, Profile>,
+}
+
+impl Profiles {
+    fn location(network: &str) -> Result<PathBuf> {
+        let mut location = dirs::config_dir().context("Cannot find the user's config directory")?;
+        location.extend([PROFILES_DIR, PROFILES_STORAGE, format!("{network}.toml").as_str()]);
+
+        Ok(location)
+    }
+
+    /// Reads the profiles of a network, none if nothing was saved yet
+    pub(crate) fn load(network: &str) -> Result<Self> {
+        let location = Self::location(network)?;
+        let mut profiles: Self = match location.exists() {
+            true => toml::from_str(&fs::read_to_string(&location)?)
+                .with_context(|| format!("Invalid profiles file {}", location.display()))?,
+            false => Self::default()
+        };
+
+        profiles.network = network.to_string();
+        Ok(profiles)
+    }
+
+    /// Writes the profiles, returns where they were written
+    pub(crate) fn save(&self) -> Result<PathBuf> {
+        let location = Self::location(&self.network)?;
+        if let Some(parent) = location.parent() {
+            fs::create_dir_all(parent)?;
+        }
+
+        fs::write(&location, toml::to_string(self)?)?;
+        Ok(location)
+    }
+
+    pub(crate) fn network(&self) -> &str {
+        &self.network
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| anyhow!(
+            "No profile named {name} on {}, see `profile list`", self.network
+        ))
+    }
+
+    pub(crate) fn contains(&self, name: &str) -> bool {
+        self.profiles.contains_key(name)
+    }
+
+    /// Saves a profile, which becomes the active one if there was none
+    pub(crate) fn insert(&mut self, name: String, profile: Profile) {
+        if self.active.is_none() {
+            self.active = Some(name.clone());
+        }
+
+        self.profiles.insert(name, profile);
+    }
+
+    /// Removes the profiles using the kiosk, returns their names
+    pub(crate) fn remove_kiosk(&mut self, kiosk: &ObjectID) -> Vec<String> {
+        let removed: Vec<_> = self.profiles.iter()
+            .filter(|(_, p)| p.setup.kiosk == *kiosk)
+            .map(|(name, _)| name.clone())
+            .collect();
+
+        for name in &removed {
+            self.profiles.remove(name);
+        }
+        if self.active.as_ref().is_some_and(|a| removed.contains(a)) {
+            self.active = None;
+        }
+
+        removed
+    }
+
+    pub(crate) fn active(&self) -> Option<(&str, &Profile)> {
+        let name = self.active.as_deref()?;
+        self.profiles.get(name).map(|p| (name, p))
+    }
+
+    pub(crate) fn set_active(&mut self, name: &str) -> Result<()> {
+        self.get(name)?;
+        self.active = Some(name.to_string());
+
+        Ok(())
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = (&String, &Profile)> {
+        self.profiles.iter()
+    }
+}
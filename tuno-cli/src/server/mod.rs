@@ -2,12 +2,13 @@ use log::info;
 use tokio::sync::oneshot;
 use tonic::transport::Server;
 use std::path::PathBuf;
+use std::sync::Arc;
 use anyhow::Result;
 
 mod tuno;
 use tuno::pb::tuno_server::TunoServer;
 
-use crate::client::{Client, Connection};
+use crate::client::Client;
 
 mod utils;
 
@@ -15,7 +16,8 @@ pub struct TunoGrpcServer {
     host: String,
     port: u16,
     identity: Option<TunoIdentity>,
-    conn: Connection
+    /// Client executing the listeners' payments, shared with the distributor's own transactions
+    client: Arc<Client>
 }
 
 #[derive(Clone)]
@@ -25,7 +27,7 @@ struct TunoIdentity {
 }
 
 impl TunoGrpcServer {
-    pub fn new(host: String, port: u16, cert_dir: Option<PathBuf>, conn: Connection) -> Self {
+    pub fn new(host: String, port: u16, cert_dir: Option<PathBuf>, client: Arc<Client>) -> Self {
         Self {
             host,
             port,
@@ -35,7 +37,7 @@ impl TunoGrpcServer {
                     key_path: dir.join("privkey.pem")
                 })
             ),
-            conn
+            client
         }
     }
 
@@ -68,8 +70,7 @@ impl TunoGrpcServer {
             }
         };
 
-        let client = Client::new(self.conn.clone())?;
-        let tuno_service = TunoServer::new(tuno::TunoService::new(client));
+        let tuno_service = TunoServer::new(tuno::TunoService::new(self.client.clone()));
         let reflection_service = tonic_reflection::server::Builder::configure()
             .register_encoded_file_descriptor_set(tuno::pb::FILE_DESCRIPTOR_SET)
             .build_v1()?;
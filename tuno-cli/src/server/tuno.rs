@@ -1,11 +1,12 @@
-use std::{io::Read, pin::Pin};
+use std::{io::Read, pin::Pin, sync::Arc};
 use log::{error, trace};
 use tokio::sync::mpsc;
 use tokio_stream::{wrappers::ReceiverStream, Stream};
 use tonic::{Request, Response, Status};
 
 use crate::client::Client;
-use crate::local_storage::get_local_song_reader;
+use crate::errors::{Error, TunoError};
+use crate::local_storage::{get_local_song_format, get_local_song_reader};
 use crate::server::utils::verify_payment;
 
 pub mod pb {
@@ -15,11 +16,11 @@ pub mod pb {
 }
 
 pub(crate) struct TunoService {
-    client: Client
+    client: Arc<Client>
 }
 
 impl TunoService {
-    pub(crate) fn new(client: Client) -> Self {
+    pub(crate) fn new(client: Arc<Client>) -> Self {
         Self { client }
     }
 }
@@ -62,9 +63,9 @@ impl pb::tuno_server::Tuno for TunoService {
             }
         };
 
-        if let Err(e) = self.client.execute_transaction(transaction).await {
-            error!("Error executing tx: {e}");
-            return Err(Status::permission_denied("Transaction failed on execution"));
+        if let Err(e) = self.client.execute_payment(transaction).await {
+            error!("Error executing tx: {e:#}");
+            return Err(execution_status(&e));
         }
 
         let mut reader = match get_local_song_reader(&song_id) {
@@ -75,11 +76,19 @@ impl pb::tuno_server::Tuno for TunoService {
             }
         };
 
+        let mime_type = match get_local_song_format(&song_id) {
+            Ok(format) => format.mime_type().to_string(),
+            Err(e) => {
+                error!("Error detecting format of {song_id}: {e}");
+                return Err(Status::internal(format!("Unsupported media format for {song_id}")));
+            }
+        };
+
         let mut data = vec![];
         match reader.read_to_end(&mut data) {
             Ok(_) => {
                 trace!("Succesful fetch request for {song_id}");
-                Ok(Response::new(pb::SongBytes { data }))
+                Ok(Response::new(pb::SongBytes { data, mime_type }))
             },
             Err(e) => {
                 error!("Error reading {song_id}: {e}");
@@ -109,9 +118,9 @@ impl pb::tuno_server::Tuno for TunoService {
             }
         };
 
-        if let Err(e) = self.client.execute_transaction(transaction).await {
-            error!("Error executing tx: {e}");
-            return Err(Status::permission_denied("Transaction failed on execution"));
+        if let Err(e) = self.client.execute_payment(transaction).await {
+            error!("Error executing tx: {e:#}");
+            return Err(execution_status(&e));
         }
 
         let mut reader = match get_local_song_reader(&song_id) {
@@ -122,13 +131,27 @@ impl pb::tuno_server::Tuno for TunoService {
             }
         };
 
+        let mime_type = match get_local_song_format(&song_id) {
+            Ok(format) => format.mime_type().to_string(),
+            Err(e) => {
+                error!("Error detecting format of {song_id}: {e}");
+                return Err(Status::internal(format!("Unsupported media format for {song_id}")));
+            }
+        };
+
         let (tx, rx) = mpsc::channel(128);
         tokio::spawn(async move {
             let mut buf = vec![0; song_stream_request.block_size as usize];
+            let mut mime_type = Some(mime_type);
 
             while let Ok(n) = reader.read(&mut buf) {
                 if n == 0 { break }
-                match tx.send(Ok(pb::SongBytes { data: buf[..n].to_vec() })).await {
+                let chunk = pb::SongBytes {
+                    data: buf[..n].to_vec(),
+                    mime_type: mime_type.take().unwrap_or_default()
+                };
+
+                match tx.send(Ok(chunk)).await {
                     Ok(_) => continue,
                     Err(e) => {
                         error!("Error while streaming: {e}");
@@ -141,4 +164,20 @@ impl pb::tuno_server::Tuno for TunoService {
         trace!("Finished stream request for {song_id}");
         Ok(Response::new(Box::pin(ReceiverStream::new(rx))))
     }
-}
\ No newline at end of file
+}
+
+/// Status of a payment rejected on execution, telling players why the contract aborted
+fn execution_status(e: &anyhow::Error) -> Status {
+    let Some(Error::Abort { error, .. }) = e.downcast_ref::<Error>() else {
+        return Status::permission_denied("Transaction failed on execution");
+    };
+
+    let message = format!("Transaction aborted: {error}");
+    match error {
+        TunoError::NotDistributor | TunoError::NotAvailable | TunoError::DisplayInUse => Status::failed_precondition(message),
+        TunoError::InsufficientAmount => Status::invalid_argument(message),
+        TunoError::NotOwner => Status::permission_denied(message),
+        TunoError::SongNotInKiosk => Status::not_found(message),
+        TunoError::AlreadyAvailable => Status::already_exists(message),
+    }
+}
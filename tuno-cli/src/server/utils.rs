@@ -7,6 +7,7 @@ use std::{fs, path::PathBuf};
 use anyhow::{bail, Result};
 
 use crate::client::Client;
+use crate::constants::{PAY_ROYALTIES_FN, TUNO_MODULE};
 
 pub fn load_tls_config(cert_path: &PathBuf, key_path: &PathBuf) -> Result<ServerTlsConfig> {
     if !cert_path.exists() {
@@ -52,12 +53,12 @@ pub fn verify_payment(
         bail!("Call does not target `{}` as package", client.package_id);
     }
 
-    if !call.module.to_string().eq("tuno") {
-        bail!("Call does not target `tuno` as module");
+    if !call.module.to_string().eq(TUNO_MODULE) {
+        bail!("Call does not target `{TUNO_MODULE}` as module");
     }
 
-    if !call.function.to_string().eq("pay_royalties") {
-        bail!("Call does not target `pay_royalties` as function");
+    if !call.function.to_string().eq(PAY_ROYALTIES_FN) {
+        bail!("Call does not target `{PAY_ROYALTIES_FN}` as function");
     }
 
     let Some(
@@ -0,0 +1,309 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use anyhow::Result;
+use iota_sdk::types::base_types::ObjectID;
+use iota_sdk::types::digests::TransactionDigest;
+use ratatui::crossterm::event::{KeyCode, KeyEvent};
+use ratatui::widgets::TableState;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::client::{Client, OwnedKiosk, Pagination};
+use crate::local_storage::{check_local_song, store_song_from_bytes, LocalCopy};
+use crate::types::{Song, SongDisplay};
+
+/// Events handled by the UI, from the terminal or from background tasks
+pub(crate) enum Message {
+    Key(KeyEvent),
+    Redraw,
+    Displays(Result<(Vec<SongDisplay>, usize)>),
+    Catalog(Result<(Vec<Song>, usize)>),
+    Details(Result<(Song, LocalCopy)>),
+    Toggled(ObjectID, Result<TransactionDigest>),
+    Progress(ObjectID, usize, usize),
+    Downloaded(ObjectID, Result<PathBuf>),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Tab {
+    Kiosk,
+    Catalog,
+}
+
+impl Tab {
+    pub(crate) const TITLES: [&'static str; 2] = ["Kiosk", "Catalog"];
+
+    pub(crate) fn index(self) -> usize {
+        match self {
+            Self::Kiosk => 0,
+            Self::Catalog => 1,
+        }
+    }
+}
+
+pub(crate) enum Download {
+    Running { received: usize, total: usize },
+    Done(PathBuf),
+    Failed(String),
+}
+
+pub(crate) struct App {
+    client: Arc<Client>,
+    tx: UnboundedSender<Message>,
+    pub(crate) kiosk: ObjectID,
+    kiosk_cap: Option<ObjectID>,
+    pub(crate) tab: Tab,
+    pub(crate) displays: Vec<SongDisplay>,
+    pub(crate) displays_state: TableState,
+    pub(crate) catalog: Vec<Song>,
+    pub(crate) catalog_state: TableState,
+    pub(crate) details: Option<(Song, LocalCopy)>,
+    pub(crate) downloads: HashMap<ObjectID, Download>,
+    pub(crate) status: String,
+    pub(crate) quit: bool,
+}
+
+impl App {
+    pub(crate) fn new(
+        client: Arc<Client>,
+        kiosk: ObjectID,
+        kiosk_cap: Option<ObjectID>,
+        tx: UnboundedSender<Message>
+    ) -> Self {
+        Self {
+            client,
+            tx,
+            kiosk,
+            kiosk_cap,
+            tab: Tab::Kiosk,
+            displays: vec![],
+            displays_state: TableState::default(),
+            catalog: vec![],
+            catalog_state: TableState::default(),
+            details: None,
+            downloads: HashMap::new(),
+            status: String::new(),
+            quit: false,
+        }
+    }
+
+    pub(crate) fn update(&mut self, message: Message) {
+        match message {
+            Message::Key(key) => self.on_key(key),
+            Message::Redraw => {},
+            Message::Displays(Ok((displays, skipped))) => {
+                self.displays = displays;
+                select_within(&mut self.displays_state, self.displays.len());
+                self.warn_skipped(skipped);
+            },
+            Message::Catalog(Ok((songs, skipped))) => {
+                self.catalog = songs;
+                select_within(&mut self.catalog_state, self.catalog.len());
+                self.warn_skipped(skipped);
+            },
+            Message::Details(Ok(details)) => self.details = Some(details),
+            Message::Toggled(song, Ok(digest)) => {
+                self.status = format!("Availability of {song} changed [{digest}]");
+                self.refresh();
+                if self.details.as_ref().is_some_and(|(s, _)| s.id == song) {
+                    self.open_details(song);
+                }
+            },
+            Message::Progress(song, received, total) => {
+                self.downloads.insert(song, Download::Running { received, total });
+            },
+            Message::Downloaded(song, Ok(location)) => {
+                self.status = format!("Song {song} downloaded to {}", location.display());
+                self.downloads.insert(song, Download::Done(location));
+                if self.details.as_ref().is_some_and(|(s, _)| s.id == song) {
+                    self.open_details(song);
+                }
+            },
+            Message::Downloaded(song, Err(e)) => {
+                self.status = format!("Download of {song} failed: {e:#}");
+                self.downloads.insert(song, Download::Failed(format!("{e:#}")));
+            },
+            Message::Displays(Err(e))
+            | Message::Catalog(Err(e))
+            | Message::Details(Err(e))
+            | Message::Toggled(_, Err(e)) => self.status = format!("Error: {e:#}"),
+        }
+    }
+
+    fn on_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Esc if self.details.is_some() => self.details = None,
+            KeyCode::Esc => self.quit = true,
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.tab = match self.tab {
+                    Tab::Kiosk => Tab::Catalog,
+                    Tab::Catalog => Tab::Kiosk,
+                };
+            },
+            KeyCode::Down | KeyCode::Char('j') => self.table_state().select_next(),
+            KeyCode::Up | KeyCode::Char('k') => self.table_state().select_previous(),
+            KeyCode::Enter => {
+                if let Some(song) = self.selected_row() {
+                    self.open_details(song);
+                }
+            },
+            KeyCode::Char('r') => {
+                self.status = "Refreshing...".to_string();
+                self.refresh();
+            },
+            KeyCode::Char('a') => self.toggle_availability(),
+            KeyCode::Char('d') => self.download(),
+            _ => {}
+        }
+    }
+
+    fn table_state(&mut self) -> &mut TableState {
+        match self.tab {
+            Tab::Kiosk => &mut self.displays_state,
+            Tab::Catalog => &mut self.catalog_state,
+        }
+    }
+
+    /// Song of the highlighted row
+    fn selected_row(&self) -> Option<ObjectID> {
+        match self.tab {
+            Tab::Kiosk => self.displays_state.selected()
+                .and_then(|i| self.displays.get(i))
+                .map(|d| d.song_id),
+            Tab::Catalog => self.catalog_state.selected()
+                .and_then(|i| self.catalog.get(i))
+                .map(|s| s.id),
+        }
+    }
+
+    /// Song on which actions apply: the opened one, or else the highlighted one
+    fn target(&self) -> Option<(ObjectID, bool)> {
+        if let Some((song, _)) = &self.details {
+            return Some((song.id, song.display_id.is_some()));
+        }
+
+        match self.tab {
+            Tab::Kiosk => self.selected_row().map(|song| (song, true)),
+            Tab::Catalog => self.catalog_state.selected()
+                .and_then(|i| self.catalog.get(i))
+                .map(|s| (s.id, s.display_id.is_some())),
+        }
+    }
+
+    fn spawn<F>(&self, task: F)
+    where
+        F: Future<Output = Message> + Send + 'static
+    {
+        let tx = self.tx.clone();
+        tokio::spawn(async move {
+            let _ = tx.send(task.await);
+        });
+    }
+
+    fn warn_skipped(&mut self, skipped: usize) {
+        if skipped > 0 {
+            self.status = format!("{skipped} malformed object(s) skipped");
+        }
+    }
+
+    /// Reloads the kiosk's displays and the owned songs
+    pub(crate) fn refresh(&mut self) {
+        let (client, kiosk) = (self.client.clone(), self.kiosk);
+        self.spawn(async move {
+            let pagination = Pagination { limit: None, cursor: None };
+            Message::Displays(
+                client.get_kiosk_songs(kiosk, &pagination).await
+                    .map(|(displays, skipped, _)| (displays.0, skipped.len()))
+            )
+        });
+
+        let client = self.client.clone();
+        self.spawn(async move {
+            let pagination = Pagination { limit: None, cursor: None };
+            Message::Catalog(
+                client.get_all_owned_songs(&pagination).await
+                    .map(|(songs, skipped, _)| (songs.0, skipped.len()))
+            )
+        });
+    }
+
+    fn open_details(&mut self, song: ObjectID) {
+        let client = self.client.clone();
+        self.spawn(async move {
+            let details = async {
+                let song = client.get_song(song).await?;
+
+                // Hashing the stored copy reads the whole file
+                Ok::<_, anyhow::Error>(tokio::task::spawn_blocking(move || -> Result<_> {
+                    let local_copy = check_local_song(&song.id.to_hex(), &song.signature)?;
+                    Ok((song, local_copy))
+                }).await??)
+            };
+
+            Message::Details(details.await)
+        });
+    }
+
+    fn toggle_availability(&mut self) {
+        let Some((song, available)) = self.target() else {
+            return;
+        };
+        let Some(kiosk_cap) = self.kiosk_cap else {
+            self.status = "Pass --kiosk-cap to change the availability of songs".to_string();
+            return;
+        };
+
+        self.status = format!(
+            "Making {song} {}...",
+            if available { "unavailable" } else { "available" }
+        );
+
+        let owned_kiosk = OwnedKiosk { kiosk: self.kiosk, kiosk_cap };
+        let client = self.client.clone();
+        self.spawn(async move {
+            let result = match available {
+                true => client.make_song_unavailable(song, owned_kiosk).await,
+                false => client.make_song_available(song, owned_kiosk).await
+            };
+
+            Message::Toggled(song, result)
+        });
+    }
+
+    fn download(&mut self) {
+        let Some((song, _)) = self.target() else {
+            return;
+        };
+        if matches!(self.downloads.get(&song), Some(Download::Running { .. })) {
+            return;
+        }
+
+        self.status = format!("Downloading {song}...");
+        self.downloads.insert(song, Download::Running { received: 0, total: 0 });
+
+        let client = self.client.clone();
+        let progress = self.tx.clone();
+        self.spawn(async move {
+            let result = client.download_song(song, |received, total| {
+                let _ = progress.send(Message::Progress(song, received, total));
+            }).await;
+
+            Message::Downloaded(
+                song,
+                result.and_then(|data| store_song_from_bytes(data, &song.to_hex()))
+            )
+        });
+    }
+}
+
+/// Keeps a row selected once the table is (re)loaded
+fn select_within(state: &mut TableState, len: usize) {
+    match (state.selected(), len) {
+        (_, 0) => state.select(None),
+        (Some(i), _) if i < len => {},
+        _ => state.select(Some(0)),
+    }
+}
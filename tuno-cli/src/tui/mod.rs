@@ -0,0 +1,59 @@
+mod app;
+mod ui;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use anyhow::Result;
+use iota_sdk::types::base_types::ObjectID;
+use ratatui::crossterm::event::{self, Event, KeyEventKind};
+use tokio::sync::mpsc;
+
+use crate::client::Client;
+use app::{App, Message};
+
+/// Runs the terminal UI until the user quits, restoring the terminal on exit
+pub(crate) async fn run(client: Client, kiosk: ObjectID, kiosk_cap: Option<ObjectID>) -> Result<()> {
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let mut app = App::new(Arc::new(client), kiosk, kiosk_cap, tx.clone());
+    app.refresh();
+
+    // Terminal events are read on a thread of their own, stopping once the UI is gone
+    let input = tx.clone();
+    tokio::task::spawn_blocking(move || -> Result<()> {
+        while !input.is_closed() {
+            if !event::poll(Duration::from_millis(200))? {
+                continue;
+            }
+
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press && input.send(Message::Key(key)).is_err() {
+                    break;
+                }
+            } else if input.send(Message::Redraw).is_err() {
+                break;
+            }
+        }
+
+        Ok(())
+    });
+    drop(tx);
+
+    let mut terminal = ratatui::init();
+    let result = async {
+        terminal.draw(|frame| ui::draw(frame, &mut app))?;
+        while let Some(message) = rx.recv().await {
+            app.update(message);
+            if app.quit {
+                break;
+            }
+
+            terminal.draw(|frame| ui::draw(frame, &mut app))?;
+        }
+
+        Ok(())
+    }.await;
+    ratatui::restore();
+
+    result
+}
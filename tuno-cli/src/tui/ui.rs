@@ -0,0 +1,155 @@
+use ratatui::Frame;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Gauge, Paragraph, Row, Table, Tabs, Wrap};
+
+use super::app::{App, Download, Tab};
+
+const HELP: &str = "q quit · tab switch · ↑↓ move · enter open · esc close · a toggle availability · d download · r refresh";
+
+pub(crate) fn draw(frame: &mut Frame, app: &mut App) {
+    let [header, main, status, help] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Min(0),
+        Constraint::Length(1),
+        Constraint::Length(1),
+    ]).areas(frame.area());
+
+    frame.render_widget(
+        Tabs::new(Tab::TITLES)
+            .select(app.tab.index())
+            .highlight_style(Style::new().bold().reversed())
+            .block(Block::bordered().title(format!(" tuno · kiosk {} ", app.kiosk))),
+        header
+    );
+
+    match app.details.is_some() {
+        true => {
+            let [list, details] = Layout::horizontal([
+                Constraint::Percentage(50),
+                Constraint::Percentage(50),
+            ]).areas(main);
+
+            draw_list(frame, app, list);
+            draw_details(frame, app, details);
+        },
+        false => draw_list(frame, app, main)
+    }
+
+    frame.render_widget(Line::from(app.status.as_str()), status);
+    frame.render_widget(Line::from(HELP).dim(), help);
+}
+
+fn draw_list(frame: &mut Frame, app: &mut App, area: Rect) {
+    let highlight = Style::new().reversed();
+
+    match app.tab {
+        Tab::Kiosk => {
+            let rows = app.displays.iter().map(|d| Row::new([
+                d.title.clone(),
+                d.artist.clone(),
+                d.genre.clone(),
+                d.streaming_price.to_string(),
+            ]));
+            let table = Table::new(rows, [
+                Constraint::Fill(3),
+                Constraint::Fill(2),
+                Constraint::Fill(2),
+                Constraint::Length(12),
+            ])
+            .header(Row::new(["Title", "Artist", "Genre", "Price"]).style(Style::new().bold()))
+            .row_highlight_style(highlight)
+            .block(Block::bordered().title(format!(" {} song(s) available ", app.displays.len())));
+
+            frame.render_stateful_widget(table, area, &mut app.displays_state);
+        },
+        Tab::Catalog => {
+            let rows = app.catalog.iter().map(|s| Row::new([
+                s.title.clone(),
+                s.artist.clone(),
+                if s.display_id.is_some() { "yes" } else { "no" }.to_string(),
+                s.distributors.0.len().to_string(),
+                s.creator_balance.to_string(),
+            ]));
+            let table = Table::new(rows, [
+                Constraint::Fill(3),
+                Constraint::Fill(2),
+                Constraint::Length(9),
+                Constraint::Length(12),
+                Constraint::Length(12),
+            ])
+            .header(Row::new(["Title", "Artist", "Available", "Distributors", "Balance"]).style(Style::new().bold()))
+            .row_highlight_style(highlight)
+            .block(Block::bordered().title(format!(" {} owned song(s) ", app.catalog.len())));
+
+            frame.render_stateful_widget(table, area, &mut app.catalog_state);
+        }
+    }
+}
+
+fn field<'a>(name: &'a str, value: String) -> Line<'a> {
+    Line::from(vec![Span::from(format!("{name}: ")).bold(), Span::from(value)])
+}
+
+fn draw_details(frame: &mut Frame, app: &App, area: Rect) {
+    let Some((song, local_copy)) = &app.details else {
+        return;
+    };
+
+    let block = Block::bordered().title(format!(" {} ", song.title));
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let [metadata, distributors, download] = Layout::vertical([
+        Constraint::Length(12),
+        Constraint::Min(3),
+        Constraint::Length(3),
+    ]).areas(inner);
+
+    let seconds = song.duration / 1000;
+    frame.render_widget(
+        Paragraph::new(vec![
+            field("Id", song.id.to_string()),
+            field("Artist", song.artist.clone()),
+            field("Album", format!("{} ({})", song.album, song.release_year)),
+            field("Genre", song.genre.clone()),
+            field("Duration", format!("{}:{:02}", seconds / 60, seconds % 60)),
+            field("Length", format!("{} bytes, {} chunk(s)", song.length, song.signature.sig.len())),
+            field("Streaming price", song.streaming_price.to_string()),
+            field("Creator balance", song.creator_balance.to_string()),
+            field("Display", song.display_id.map_or("not available".to_string(), |id| id.to_string())),
+            field("Local copy", local_copy.to_string()),
+        ]).wrap(Wrap { trim: true }),
+        metadata
+    );
+
+    let rows = song.distributors.0.iter().map(|(address, d)| Row::new([
+        address.to_string(),
+        d.url.clone(),
+        d.streaming_price.to_string(),
+        d.balance.to_string(),
+    ]));
+    frame.render_widget(
+        Table::new(rows, [
+            Constraint::Fill(2),
+            Constraint::Fill(2),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ])
+        .header(Row::new(["Distributor", "Url", "Price", "Balance"]).style(Style::new().bold()))
+        .block(Block::bordered().title(format!(" {} distributor(s) ", song.distributors.0.len()))),
+        distributors
+    );
+
+    let gauge = Gauge::default().block(Block::bordered().title(" Download "));
+    let gauge = match app.downloads.get(&song.id) {
+        Some(Download::Running { received, total }) => gauge
+            .ratio(if *total == 0 { 0.0 } else { (*received as f64 / *total as f64).min(1.0) })
+            .label(format!("{received}/{total} bytes")),
+        Some(Download::Done(location)) => gauge.ratio(1.0).label(location.display().to_string()),
+        Some(Download::Failed(e)) => gauge.ratio(0.0).label(format!("failed: {e}")),
+        None => gauge.ratio(0.0).label("press d to download"),
+    };
+    frame.render_widget(gauge, download);
+}
@@ -1,8 +1,17 @@
-use anyhow::Result;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+
+use anyhow::{bail, Result};
 use clap::{CommandFactory, Parser};
+use iota_sdk::types::base_types::ObjectID;
+use log::warn;
 
 use crate::{
-    distribution_commands::DistributionCommands, kiosk_commands::KioskCommands, music_commands::MusicCommands
+    client::{Client, Connection, KioskArgs},
+    distribution_commands::DistributionCommands, kiosk_commands::KioskCommands, music_commands::MusicCommands,
+    output::OutputFormat, player::{self, Control, SinkTarget, StreamBuffer}, profile_commands::ProfileCommands, tui,
+    wallet_commands::WalletCommands
 };
 
 #[derive(Parser)]
@@ -24,16 +33,63 @@ pub enum TunoCommands {
         #[command(subcommand)]
         cmd: Option<KioskCommands>
     },
+
+    /// Client for managing the active address' coins
+    Wallet {
+        #[command(subcommand)]
+        cmd: Option<WalletCommands>
+    },
+
+    /// Manage the creator profiles saved for the wallet's network
+    Profile {
+        #[command(subcommand)]
+        cmd: Option<ProfileCommands>
+    },
+
+    /// Keep a local index of the package's events up to date
+    Index {
+        /// Seconds to wait between two event queries
+        #[arg(long, default_value = "5")]
+        interval: u64,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Pay a distributor of a song and listen to it while it is streamed
+    Listen {
+        /// Song's object id
+        #[arg(long)]
+        song: ObjectID,
+
+        /// Write the decoded song to a WAV file instead of playing it on the audio device
+        #[arg(long)]
+        wav: Option<PathBuf>,
+
+        #[command(flatten)]
+        conn: Connection
+    },
+
+    /// Browse a kiosk and manage the active address' catalog in the terminal
+    Tui {
+        /// Kiosk to browse (default: the profile's, or the only one owned), its owner capability
+        /// is required to change the availability of songs
+        #[command(flatten)]
+        kiosk_args: KioskArgs,
+
+        #[command(flatten)]
+        conn: Connection
+    },
 }
 
 impl TunoCommands {
-    pub async fn execute(self) -> Result<()> {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
         match self {
             TunoCommands::Distribution {
                 cmd
             } => {
                 if let Some(cmd) = cmd {
-                    cmd.execute().await?;
+                    cmd.execute(output).await?;
                 } else {
                     let mut app = TunoCommands::command();
                     app.build();
@@ -47,7 +103,7 @@ impl TunoCommands {
                 cmd
             } => {
                 if let Some(cmd) = cmd {
-                    cmd.execute().await?;
+                    cmd.execute(output).await?;
                 } else {
                     let mut app = TunoCommands::command();
                     app.build();
@@ -61,7 +117,7 @@ impl TunoCommands {
                 cmd
             } => {
                 if let Some(cmd) = cmd {
-                    cmd.execute().await?;
+                    cmd.execute(output).await?;
                 } else {
                     let mut app = TunoCommands::command();
                     app.build();
@@ -70,6 +126,96 @@ impl TunoCommands {
 
                 Ok(())
             }
+
+            TunoCommands::Wallet {
+                cmd
+            } => {
+                if let Some(cmd) = cmd {
+                    cmd.execute(output).await?;
+                } else {
+                    let mut app = TunoCommands::command();
+                    app.build();
+                    app.find_subcommand_mut("wallet").unwrap().print_help()?;
+                }
+
+                Ok(())
+            }
+
+            TunoCommands::Profile {
+                cmd
+            } => {
+                if let Some(cmd) = cmd {
+                    cmd.execute(output).await?;
+                } else {
+                    let mut app = TunoCommands::command();
+                    app.build();
+                    app.find_subcommand_mut("profile").unwrap().print_help()?;
+                }
+
+                Ok(())
+            }
+
+            TunoCommands::Index {
+                interval,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+
+                eprintln!("Indexing events of {}...", client.package_id);
+                client.run_indexer(Duration::from_secs(interval)).await?;
+
+                Ok(())
+            }
+
+            TunoCommands::Listen {
+                song,
+                wav,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let obj = client.get_song(song).await?;
+                eprintln!("Listening to {} by {}", obj.title, obj.artist);
+                eprintln!("{}", Control::HELP);
+
+                let (controls_tx, controls_rx) = mpsc::channel();
+                player::read_controls(controls_tx);
+
+                let buffer = StreamBuffer::new(obj.length);
+                let source = buffer.reader();
+                let target = wav.map_or(SinkTarget::Device, SinkTarget::Wav);
+                let mut playing = player::start(source, target, controls_rx).await?;
+
+                let streamed = tokio::select! {
+                    streamed = client.stream_song(song, |chunk, _| buffer.push(chunk)) => streamed,
+                    // Stopped before the end of the stream
+                    played = &mut playing => return Ok(played??),
+                };
+
+                buffer.close(streamed.as_ref().err().map(|e| format!("{e:#}")));
+                streamed?;
+                playing.await?
+            }
+
+            TunoCommands::Tui {
+                kiosk_args,
+                conn
+            } => {
+                let client = Client::connect(conn).await?;
+                let owned_kiosk = match client.find_kiosk(&kiosk_args).await {
+                    Ok(owned_kiosk) => owned_kiosk,
+                    // Browsing a kiosk of another address
+                    Err(e) if kiosk_args.kiosk.is_some() => {
+                        warn!("{e:#}, songs' availability cannot be changed");
+                        None
+                    },
+                    Err(e) => return Err(e)
+                };
+
+                let Some(kiosk) = kiosk_args.kiosk.or(owned_kiosk.map(|o| o.kiosk)) else {
+                    bail!("Active address owns no kiosk, give one to browse with --kiosk");
+                };
+                tui::run(client, kiosk, owned_kiosk.map(|o| o.kiosk_cap)).await
+            }
         }
     }
 }
@@ -1,17 +1,18 @@
 use std::collections::BTreeMap;
 use std::io::{BufReader, Read};
-use std::{fs, path::PathBuf};
+use std::path::PathBuf;
 use sha2::{Sha256, Digest};
+use serde::{Serialize, Serializer};
 
-use iota_sdk::{
-    rpc_types::{IotaMoveStruct, IotaMoveValue},
-    types::base_types::{IotaAddress, ObjectID}
-};
+use iota_sdk::types::base_types::{IotaAddress, ObjectID};
+use iota_sdk::types::collection_types::VecMap;
 
 use crate::constants::TUNO_BASE_CHUNK_SIZE;
+use crate::media::{self, MediaError};
+use crate::objects::{MoveCreatorCap, MoveDistributor, MoveKiosk, MoveKioskOwnerCap, MoveSong, MoveSongDisplay};
 
 #[allow(dead_code)]
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Song {
     pub id: ObjectID,
     pub title: String,
@@ -30,32 +31,30 @@ pub struct Song {
     pub display_id: Option<ObjectID>,
 }
 
-impl From<IotaMoveStruct> for Song {
-    fn from(s: IotaMoveStruct) -> Self {
+impl From<MoveSong> for Song {
+    fn from(s: MoveSong) -> Self {
         Self {
-            id: parse_uid(&s, "id"),
-            title: parse_string(&s, "title"),
-            artist: parse_string(&s, "artist"),
-            album: parse_string(&s, "album"),
-            release_year: parse_string(&s, "release_year").parse().unwrap(),
-            genre: parse_string(&s, "genre"),
-            cover_art_url: parse_string(&s, "cover_art_url"),
-            streaming_price: parse_string(&s, "streaming_price").parse().unwrap(),
-            owner: parse_address(&s, "owner"),
-            length: parse_string(&s, "length").parse().unwrap(),
-            duration: parse_string(&s, "duration").parse().unwrap(),
-            signature: TunoSignature::from(parse_vec(&s, "signature")),
-            creator_balance: parse_string(&s, "creator_balance").parse().unwrap(),
-            distributors: DistributionMap::from(parse_struct(&s, "distributors")),
-            display_id: match s.read_dynamic_field_value("display_id") {
-                Some(IotaMoveValue::Address(a)) => Some(ObjectID::from(a)),
-                Some(IotaMoveValue::Option(_)) => None,
-                _ => panic!("Error parsing display_id from {s}")
-            }
+            id: *s.id.object_id(),
+            title: s.title,
+            artist: s.artist,
+            album: s.album,
+            release_year: s.release_year as usize,
+            genre: s.genre,
+            cover_art_url: s.cover_art_url,
+            streaming_price: s.streaming_price as usize,
+            owner: s.owner,
+            length: s.length as usize,
+            duration: s.duration as usize,
+            signature: TunoSignature::from(s.signature),
+            creator_balance: s.creator_balance.value() as usize,
+            distributors: DistributionMap::from(s.distributors),
+            display_id: s.display_id.map(|id| id.bytes)
         }
     }
 }
 
+#[derive(Serialize)]
+#[serde(transparent)]
 pub struct SongList(pub Vec<Song>);
 
 impl FromIterator<Song> for SongList {
@@ -70,14 +69,16 @@ pub struct TunoSignature {
     _index: usize
 }
 
-impl From<&PathBuf> for TunoSignature {
-    fn from(path: &PathBuf) -> Self {
-        let file = fs::File::open(path).expect("failed to open media");
-        let mut reader = BufReader::new(file);
+impl TryFrom<&PathBuf> for TunoSignature {
+    type Error = MediaError;
+
+    fn try_from(path: &PathBuf) -> Result<Self, Self::Error> {
+        let mut reader = BufReader::new(media::open(path)?);
         let mut sig = vec![];
     
         let mut buf = vec![0; TUNO_BASE_CHUNK_SIZE];
-        while let Ok(n) = reader.read(&mut buf) {
+        loop {
+            let n = reader.read(&mut buf).map_err(|e| MediaError::io(path, e))?;
             if n == 0 { break }
     
             let mut hasher = Sha256::new();
@@ -86,22 +87,20 @@ impl From<&PathBuf> for TunoSignature {
             sig.push(hasher.finalize().to_vec());
         }
     
-        Self { sig: sig, _index: 0 }
+        Ok(Self { sig: sig, _index: 0 })
     }
 }
 
-impl From<Vec<IotaMoveValue>> for TunoSignature {
-    fn from(sig: Vec<IotaMoveValue>) -> Self {
-        Self {
-            sig: sig.iter().map(|s| match s {
-                IotaMoveValue::Vector(inner) => inner.iter().map(|i| match i {
-                    IotaMoveValue::Number(n) => *n as u8,
-                    _ => panic!("Error parsing signature number from {:?}", inner)
-                }).collect(),
-                _ => panic!("Error parsing inner signature from {s}")
-            }).collect(),
-            _index: 0
-        }
+/// Serialized as the hex digest of each chunk
+impl Serialize for TunoSignature {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.sig.iter().map(hex::encode))
+    }
+}
+
+impl From<Vec<Vec<u8>>> for TunoSignature {
+    fn from(sig: Vec<Vec<u8>>) -> Self {
+        Self { sig, _index: 0 }
     }
 }
 
@@ -119,6 +118,7 @@ impl TunoSignature {
         Some(data)
     }
 
+    /// Whether the chunk at `index` matches the signature, never for chunks past its end
     pub(crate) fn check_sig_at(
         &self,
         data: Vec<u8>,
@@ -129,11 +129,12 @@ impl TunoSignature {
         let mut hasher = Sha256::new();
         hasher.update(data);
 
-        self.sig.get(index).unwrap() == &hasher.finalize().to_vec()
+        self.sig.get(index).is_some_and(|sig| *sig == hasher.finalize().to_vec())
     }
 }
 
 #[allow(dead_code)]
+#[derive(Serialize)]
 pub struct SongDisplay {
     pub id: ObjectID,
     pub song_id: ObjectID,
@@ -144,20 +145,22 @@ pub struct SongDisplay {
     pub cover_art_url: String,
 }
 
-impl From<IotaMoveStruct> for SongDisplay {
-    fn from(s: IotaMoveStruct) -> Self {
+impl From<MoveSongDisplay> for SongDisplay {
+    fn from(s: MoveSongDisplay) -> Self {
         Self {
-            id: parse_uid(&s, "id"),
-            song_id: ObjectID::from(parse_address(&s, "song_id")),
-            title: parse_string(&s, "title"),
-            artist: parse_string(&s, "artist"),
-            genre: parse_string(&s, "genre"),
-            streaming_price: parse_string(&s, "streaming_price").parse().unwrap(),
-            cover_art_url: parse_string(&s, "cover_art_url"),
+            id: *s.id.object_id(),
+            song_id: s.song_id.bytes,
+            title: s.title,
+            artist: s.artist,
+            genre: s.genre,
+            streaming_price: s.streaming_price as usize,
+            cover_art_url: s.cover_art_url,
         }
     }
 }
 
+#[derive(Serialize)]
+#[serde(transparent)]
 pub struct SongDisplayList(pub Vec<SongDisplay>);
 
 impl FromIterator<SongDisplay> for SongDisplayList {
@@ -166,7 +169,7 @@ impl FromIterator<SongDisplay> for SongDisplayList {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
 pub struct Distributor {
     pub url: String,
     pub joined_at: usize,
@@ -174,31 +177,27 @@ pub struct Distributor {
     pub balance: usize
 }
 
-impl From<IotaMoveStruct> for Distributor {
-    fn from(s: IotaMoveStruct) -> Self {
+impl From<MoveDistributor> for Distributor {
+    fn from(s: MoveDistributor) -> Self {
         Self {
-            url: parse_string(&s, "url"),
-            joined_at: parse_string(&s, "joined_at").parse().unwrap(),
-            streaming_price: parse_string(&s, "streaming_price").parse().unwrap(),
-            balance: parse_string(&s, "balance").parse().unwrap()
+            url: s.url,
+            joined_at: s.joined_at as usize,
+            streaming_price: s.streaming_price as usize,
+            balance: s.balance.value() as usize
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(transparent)]
 pub struct DistributionMap(pub BTreeMap<IotaAddress, Distributor>);
 
-impl From<IotaMoveStruct> for DistributionMap {
-    fn from(s: IotaMoveStruct) -> Self {
+impl From<VecMap<IotaAddress, MoveDistributor>> for DistributionMap {
+    fn from(map: VecMap<IotaAddress, MoveDistributor>) -> Self {
         Self(
-            parse_vec(&s, "contents").into_iter()
-                .map(|entry| match entry {
-                    IotaMoveValue::Struct(s) => (
-                        parse_address(&s, "key"),
-                        Distributor::from(parse_struct(&s, "value"))
-                    ),
-                    _ => panic!("Error parsing {entry}")
-                }).collect()
+            map.contents.into_iter()
+                .map(|entry| (entry.key, Distributor::from(entry.value)))
+                .collect()
         )
     }
 }
@@ -209,37 +208,86 @@ impl DistributionMap {
     }
 }
 
-fn parse_uid(s: &IotaMoveStruct, field_name: &str) -> ObjectID {
-    match s.read_dynamic_field_value(field_name) {
-        Some(IotaMoveValue::UID { id }) => id,
-        _ => panic!("Error parsSing {field_name} from {s}")
-    }
+#[derive(Serialize)]
+pub struct KioskInfo {
+    pub id: ObjectID,
+    pub owner: IotaAddress,
+    pub item_count: usize,
+    /// Profits of the kiosk's sales, in NANOS
+    pub profits: usize,
 }
 
-fn parse_string(s: &IotaMoveStruct, field_name: &str) -> String {
-    match s.read_dynamic_field_value(field_name) {
-        Some(IotaMoveValue::String(parsed)) => parsed,
-        _ => panic!("Error parsing {field_name} from {s}")
+impl From<MoveKiosk> for KioskInfo {
+    fn from(k: MoveKiosk) -> Self {
+        Self {
+            id: *k.id.object_id(),
+            owner: k.owner,
+            item_count: k.item_count as usize,
+            profits: k.profits.value() as usize
+        }
     }
 }
 
-fn parse_address(s: &IotaMoveStruct, field_name: &str) -> IotaAddress {
-    match s.read_dynamic_field_value(field_name) {
-        Some(IotaMoveValue::Address(parsed)) => parsed,
-        _ => panic!("Error parsing {field_name} from {s}")
+#[derive(Serialize)]
+pub struct CreatorCap {
+    pub id: ObjectID,
+    pub creator: IotaAddress,
+}
+
+impl From<MoveCreatorCap> for CreatorCap {
+    fn from(c: MoveCreatorCap) -> Self {
+        Self {
+            id: *c.id.object_id(),
+            creator: c.creator
+        }
     }
 }
 
-fn parse_struct(s: &IotaMoveStruct, field_name: &str) -> IotaMoveStruct {
-    match s.read_dynamic_field_value(field_name) {
-        Some(IotaMoveValue::Struct(parsed)) => parsed,
-        _ => panic!("Error parsing {field_name} from {s}")
+#[derive(Serialize)]
+pub struct KioskCap {
+    pub id: ObjectID,
+    pub kiosk: ObjectID,
+}
+
+impl From<MoveKioskOwnerCap> for KioskCap {
+    fn from(c: MoveKioskOwnerCap) -> Self {
+        Self {
+            id: *c.id.object_id(),
+            kiosk: c.kiosk.bytes
+        }
     }
 }
 
-fn parse_vec(s: &IotaMoveStruct, field_name: &str) -> Vec<IotaMoveValue> {
-    match s.read_dynamic_field_value(field_name) {
-        Some(IotaMoveValue::Vector(parsed)) => parsed,
-        _ => panic!("Error parsing {field_name} from {s}")
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signature(chunks: &[&[u8]]) -> TunoSignature {
+        TunoSignature::from(chunks.iter().map(|c| Sha256::digest(c).to_vec()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn consumes_signed_chunks() {
+        let chunks = [vec![1; TUNO_BASE_CHUNK_SIZE], vec![2; 10]];
+        let mut sig = signature(&[&chunks[0], &chunks[1]]);
+
+        assert_eq!(sig.consume_data(chunks[0].clone()), Some(chunks[0].clone()));
+        assert_eq!(sig.consume_data(chunks[1].clone()), Some(chunks[1].clone()));
+    }
+
+    #[test]
+    fn rejects_chunks_past_the_signature() {
+        let chunk = vec![1; TUNO_BASE_CHUNK_SIZE];
+        let mut sig = signature(&[&chunk]);
+
+        assert!(!sig.check_sig_at(chunk.clone(), 1));
+        assert_eq!(sig.consume_data([chunk.clone(), chunk].concat()), None);
+    }
+
+    #[test]
+    fn rejects_altered_chunks() {
+        let mut sig = signature(&[&[1, 2, 3]]);
+
+        assert_eq!(sig.consume_data(vec![1, 2, 4]), None);
     }
 }
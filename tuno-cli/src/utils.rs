@@ -1,7 +1,14 @@
+use std::future::Future;
+use std::io::{self, Write as _};
+use std::num::NonZeroUsize;
 use std::str::FromStr as _;
 
-use iota_sdk::rpc_types::{IotaObjectData, IotaObjectDataFilter, IotaObjectDataOptions, IotaObjectResponse, IotaObjectResponseQuery, IotaTransactionBlockResponse, ObjectChange};
-use iota_sdk::types::coin::Coin;
+use futures::{stream, Stream, TryStreamExt as _};
+
+use iota_sdk::rpc_types::{
+    Coin, IotaObjectData, IotaObjectDataFilter, IotaObjectDataOptions, IotaObjectResponse, IotaObjectResponseQuery,
+    IotaTransactionBlockResponse, ObjectChange, Page
+};
 use iota_sdk::types::transaction::ObjectArg;
 use iota_sdk::types::base_types::{IotaAddress, ObjectID};
 use iota_sdk::types::object::Owner;
@@ -10,8 +17,10 @@ use iota_sdk::types::IOTA_FRAMEWORK_PACKAGE_ID;
 use iota_sdk::wallet_context::WalletContext;
 
 use anyhow::{bail, Result};
+use move_core_types::language_storage::StructTag;
 
-use crate::constants::USDC_TYPE_TAG_STR;
+use crate::client::Pagination;
+use crate::constants::{MULTI_GET_OBJECT_MAX_SIZE, USDC_TYPE_TAG_STR};
 
 pub(crate) fn extract_created_cap(
     resp: &IotaTransactionBlockResponse,
@@ -27,6 +36,24 @@ pub(crate) fn extract_created_song(
     extract_created_object(resp, "Song", package_id)
 }
 
+/// Songs created by the transaction, in the order of their creation
+pub(crate) fn extract_created_songs(
+    resp: &IotaTransactionBlockResponse,
+    package_id: ObjectID
+) -> Result<Vec<ObjectID>> {
+    let Some(events) = &resp.events else {
+        bail!("Can't find events of {}", resp.digest);
+    };
+
+    events.data.iter()
+        .filter(|event| ObjectID::from(event.type_.address) == package_id)
+        .filter(|event| event.type_.name.as_str() == "SongCreated")
+        .map(|event| match event.parsed_json["id"].as_str() {
+            Some(id) => Ok(ObjectID::from_hex_literal(id)?),
+            None => bail!("Error parsing song id from {}", event.parsed_json)
+        }).collect()
+}
+
 pub(crate) fn extract_created_kiosk(resp: &IotaTransactionBlockResponse) -> Result<ObjectID> {
     extract_created_object(resp, "Kiosk", IOTA_FRAMEWORK_PACKAGE_ID)
 }
@@ -105,55 +132,84 @@ pub(crate) async fn get_shared_object_ref(
     Ok(ObjectArg::SharedObject { id, initial_shared_version, mutable })
 }
 
-pub(crate) async fn query_owned_songs(
-    _wallet: &WalletContext,
-    _package_id: ObjectID
-) -> Result<Vec<IotaObjectResponse>> {
-    todo!("Get all events emited filter by owner")
+pub(crate) async fn query_usdc_coins(address: IotaAddress, wallet: &WalletContext) -> Result<Vec<Coin>> {
+    let client = wallet.get_client().await?;
+    let coin_api = client.coin_read_api();
+
+    paginate(None, |cursor| coin_api.get_coins(
+        address,
+        Some(USDC_TYPE_TAG_STR.to_string()),
+        cursor,
+        None
+    )).try_collect().await
 }
 
-pub(crate) async fn query_usdc_coins(address: IotaAddress, wallet: &WalletContext) -> Result<Vec<IotaObjectResponse>> {
-    let response = wallet.get_client().await?
-        .read_api()
-        .get_owned_objects(
-            address,
-            IotaObjectResponseQuery {
-                filter: Some(IotaObjectDataFilter::StructType(
-                    Coin::type_(get_usdc_type_tag()?)
-                )),
-                options: Some(IotaObjectDataOptions::new().with_content())
-            },
-            None,
-            None
-        ).await?;
-        // TODO: Deal with next page
+pub(crate) async fn query_gas_coins(address: IotaAddress, wallet: &WalletContext) -> Result<Vec<Coin>> {
+    let client = wallet.get_client().await?;
+    let coin_api = client.coin_read_api();
+
+    paginate(None, |cursor| coin_api.get_coins(
+        address,
+        None,
+        cursor,
+        None
+    )).try_collect().await
+}
+
+/// Picks the smallest gas coin able to cover `budget` on its own, leaving the
+/// larger ones free for other transactions
+pub(crate) fn select_gas_coin(coins: Vec<Coin>, budget: u64) -> Result<Coin> {
+    let largest = coins.iter().map(|c| c.balance).max();
 
-    Ok(response.data)
+    match coins.into_iter().filter(|c| c.balance >= budget).min_by_key(|c| c.balance) {
+        Some(coin) => Ok(coin),
+        None => match largest {
+            Some(largest) => bail!(
+                "Insufficient gas: a budget of {budget} NANOS is required but the largest gas coin holds {largest}"
+            ),
+            None => bail!("Insufficient gas: no gas coin owned")
+        }
+    }
+}
+
+/// Picks the coins to pay `amount` with, preferring the smallest coin that covers what
+/// is still missing so that the leftover dust is minimized, `None` if the balance is insufficient
+pub(crate) fn select_coins(mut coins: Vec<Coin>, amount: u64) -> Option<Vec<Coin>> {
+    coins.sort_by_key(|c| c.balance);
+
+    let mut selected = vec![];
+    let mut total = 0;
+    while total < amount {
+        let missing = amount - total;
+        let i = coins.iter()
+            .position(|c| c.balance >= missing)
+            .unwrap_or(coins.len().checked_sub(1)?);
+
+        total += coins[i].balance;
+        selected.push(coins.remove(i));
+    }
+
+    Some(selected)
 }
 
 pub(crate) async fn query_kiosk_songs(
     wallet: &WalletContext,
-    kiosk: ObjectID
-) -> Result<Vec<IotaObjectResponse>> {
+    kiosk: ObjectID,
+    pagination: &Pagination
+) -> Result<(Vec<IotaObjectResponse>, Option<ObjectID>)> {
     let client = wallet.get_client().await?;
-    let fields = client
-        .read_api()
-        .get_dynamic_fields(kiosk, None, None).await?
-        .data;
-    // TODO: Deal with next page
-
-    let mut displays = vec![];
-    for f in fields {
-        displays.push(
-            client.read_api()
-                .get_object_with_options(
-                    f.object_id,
-                    IotaObjectDataOptions::new().with_content()
-                ).await?
-        );
-    }
+    let read_api = client.read_api();
+    // Items are dynamic object fields: their `object_id` is the child's, not a cursor of the query
+    let (fields, next_cursor) = fetch_page(
+        pagination.cursor,
+        pagination.limit,
+        |cursor, limit| read_api.get_dynamic_fields(kiosk, cursor, limit)
+    ).await?;
 
-    Ok(displays)
+    Ok((
+        query_objects(wallet, fields.into_iter().map(|f| f.object_id).collect()).await?,
+        next_cursor
+    ))
 }
 
 pub(crate) async fn query_object(
@@ -164,12 +220,225 @@ pub(crate) async fn query_object(
         .read_api()
         .get_object_with_options(
             song,
-            IotaObjectDataOptions::new().with_content()
+            IotaObjectDataOptions::new().with_bcs()
         ).await?;
 
     Ok(response)
 }
 
+pub(crate) async fn query_objects(
+    wallet: &WalletContext,
+    ids: Vec<ObjectID>
+) -> Result<Vec<IotaObjectResponse>> {
+    let client = wallet.get_client().await?;
+    let mut objects = vec![];
+    for chunk in ids.chunks(MULTI_GET_OBJECT_MAX_SIZE) {
+        objects.append(
+            &mut client.read_api()
+                .multi_get_object_with_options(
+                    chunk.to_vec(),
+                    IotaObjectDataOptions::new().with_bcs()
+                ).await?
+        );
+    }
+
+    Ok(objects)
+}
+
+/// Objects of type `type_` owned by `address`
+pub(crate) async fn query_owned_objects(
+    wallet: &WalletContext,
+    address: IotaAddress,
+    type_: StructTag
+) -> Result<Vec<IotaObjectResponse>> {
+    let client = wallet.get_client().await?;
+    let read_api = client.read_api();
+    let query = IotaObjectResponseQuery::new(
+        Some(IotaObjectDataFilter::StructType(type_)),
+        Some(IotaObjectDataOptions::new().with_bcs())
+    );
+
+    paginate(None, |cursor| read_api.get_owned_objects(
+        address,
+        Some(query.clone()),
+        cursor,
+        None
+    )).try_collect().await
+}
+
+/// Streams every item of a paginated read API query, starting after `cursor`
+pub(crate) fn paginate<T, C, E, F, Fut>(
+    cursor: Option<C>,
+    fetch: F
+) -> impl Stream<Item = Result<T>>
+where
+    F: FnMut(Option<C>) -> Fut,
+    Fut: Future<Output = Result<Page<T, C>, E>>,
+    anyhow::Error: From<E>
+{
+    stream::try_unfold((fetch, Some(cursor)), |(mut fetch, cursor)| async move {
+        let Some(cursor) = cursor else {
+            return Ok::<_, anyhow::Error>(None);
+        };
+
+        let page = fetch(cursor).await?;
+        let next = if page.has_next_page {
+            page.next_cursor.map(Some)
+        } else {
+            None
+        };
+
+        Ok(Some((
+            stream::iter(page.data.into_iter().map(Ok::<T, anyhow::Error>)),
+            (fetch, next)
+        )))
+    }).try_flatten()
+}
+
+/// Fetches up to `limit` items of a paginated read API query, starting after `cursor`. No more
+/// items than remain are requested, so that the cursor of the last page resumes right after them
+pub(crate) async fn fetch_page<T, C, E, F, Fut>(
+    mut cursor: Option<C>,
+    limit: Option<NonZeroUsize>,
+    mut fetch: F
+) -> Result<(Vec<T>, Option<C>)>
+where
+    F: FnMut(Option<C>, Option<usize>) -> Fut,
+    Fut: Future<Output = Result<Page<T, C>, E>>,
+    anyhow::Error: From<E>
+{
+    let mut items = vec![];
+    loop {
+        let remaining = limit.map(|l| l.get() - items.len());
+        let mut page = fetch(cursor, remaining).await?;
+        items.append(&mut page.data);
+
+        let next = page.next_cursor.filter(|_| page.has_next_page);
+        match next {
+            Some(next) if limit.is_none_or(|l| items.len() < l.get()) => cursor = Some(next),
+            next => return Ok((items, next))
+        }
+    }
+}
+
 pub(crate) fn get_usdc_type_tag() -> Result<TypeTag> {
     TypeTag::from_str(USDC_TYPE_TAG_STR)
 }
+
+/// Asks a yes/no question on the terminal, defaulting to no
+pub(crate) fn confirm(question: &str) -> Result<bool> {
+    eprint!("{question} [y/N] ");
+    io::stderr().flush()?;
+
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer)?;
+
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::num::NonZeroUsize;
+
+    use futures::TryStreamExt as _;
+    use iota_sdk::rpc_types::Page;
+    use iota_sdk::types::base_types::ObjectID;
+
+    use super::{fetch_page, paginate};
+
+    /// Read API serving `ids` by pages of at most `max_page` items, its cursor being the last item
+    struct MockReadApi {
+        ids: Vec<ObjectID>,
+        max_page: usize,
+        requests: RefCell<usize>,
+    }
+
+    impl MockReadApi {
+        fn new(len: usize, max_page: usize) -> Self {
+            Self {
+                ids: (0..len).map(|_| ObjectID::random()).collect(),
+                max_page,
+                requests: RefCell::new(0),
+            }
+        }
+
+        async fn query(
+            &self,
+            cursor: Option<ObjectID>,
+            limit: Option<usize>
+        ) -> anyhow::Result<Page<ObjectID, ObjectID>> {
+            *self.requests.borrow_mut() += 1;
+
+            let start = match cursor {
+                Some(cursor) => self.ids.iter().position(|id| *id == cursor).unwrap() + 1,
+                None => 0
+            };
+            let end = self.ids.len().min(start + limit.unwrap_or(usize::MAX).min(self.max_page));
+            let data = self.ids[start..end].to_vec();
+
+            Ok(Page {
+                next_cursor: data.last().copied(),
+                has_next_page: end < self.ids.len(),
+                data,
+            })
+        }
+    }
+
+    fn limit(n: usize) -> Option<NonZeroUsize> {
+        NonZeroUsize::new(n)
+    }
+
+    #[tokio::test]
+    async fn paginate_streams_every_page() {
+        let api = MockReadApi::new(7, 3);
+        let ids: Vec<_> = paginate(None, |cursor| api.query(cursor, None)).try_collect().await.unwrap();
+
+        assert_eq!(ids, api.ids);
+        assert_eq!(*api.requests.borrow(), 3);
+    }
+
+    #[tokio::test]
+    async fn paginate_resumes_after_cursor() {
+        let api = MockReadApi::new(5, 2);
+        let ids: Vec<_> = paginate(Some(api.ids[1]), |cursor| api.query(cursor, None)).try_collect().await.unwrap();
+
+        assert_eq!(ids, api.ids[2..]);
+    }
+
+    #[tokio::test]
+    async fn fetch_page_without_limit_fetches_everything() {
+        let api = MockReadApi::new(7, 3);
+        let (ids, cursor) = fetch_page(None, None, |cursor, limit| api.query(cursor, limit)).await.unwrap();
+
+        assert_eq!(ids, api.ids);
+        assert_eq!(cursor, None);
+    }
+
+    #[tokio::test]
+    async fn fetch_page_cursor_resumes_after_the_last_item() {
+        let api = MockReadApi::new(10, 3);
+
+        let (first, cursor) = fetch_page(None, limit(4), |cursor, limit| api.query(cursor, limit)).await.unwrap();
+        assert_eq!(first, api.ids[..4]);
+        assert_eq!(cursor, Some(api.ids[3]));
+
+        let (second, cursor) = fetch_page(cursor, limit(4), |cursor, limit| api.query(cursor, limit)).await.unwrap();
+        assert_eq!(second, api.ids[4..8]);
+        assert_eq!(cursor, Some(api.ids[7]));
+
+        let (last, cursor) = fetch_page(cursor, limit(4), |cursor, limit| api.query(cursor, limit)).await.unwrap();
+        assert_eq!(last, api.ids[8..]);
+        assert_eq!(cursor, None);
+    }
+
+    #[tokio::test]
+    async fn fetch_page_has_no_cursor_when_the_limit_reaches_the_end() {
+        let api = MockReadApi::new(6, 3);
+        let (ids, cursor) = fetch_page(None, limit(6), |cursor, limit| api.query(cursor, limit)).await.unwrap();
+
+        assert_eq!(ids, api.ids);
+        assert_eq!(cursor, None);
+        assert_eq!(*api.requests.borrow(), 2);
+    }
+}
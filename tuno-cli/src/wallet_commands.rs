@@ -0,0 +1,35 @@
+use anyhow::Result;
+use clap::Parser;
+
+use crate::client::{Client, Connection};
+use crate::displays::Consolidation;
+use crate::output::{print_output, OutputFormat};
+
+#[derive(Parser)]
+pub enum WalletCommands {
+    /// Merge all USDC coins of the active address into a single one
+    Consolidate {
+        #[command(flatten)]
+        conn: Connection
+    }
+}
+
+impl WalletCommands {
+    pub async fn execute(self, output: OutputFormat) -> Result<()> {
+        match self {
+            WalletCommands::Consolidate {
+                conn
+            } => {
+                // Merging coins only calls the framework, the package is not checked
+                let client = Client::new(conn)?;
+
+                let consolidation = match client.consolidate_usdc_coins().await? {
+                    Some((coin, digests)) => Consolidation { coin: Some(coin), digests },
+                    None => Consolidation { coin: None, digests: vec![] }
+                };
+
+                print_output(&consolidation, output)
+            }
+        }
+    }
+}
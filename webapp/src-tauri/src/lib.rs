@@ -1,7 +1,8 @@
 use std::str::FromStr as _;
 
 use iota_sdk::types::base_types::ObjectID;
-use tuno_cli::client::{Client, Connection};
+use tuno_cli::client::{Client, Connection, GasConfig};
+use tuno_cli::errors::Error;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -9,8 +10,9 @@ pub fn run() {
     panic!("Couldn't create package_id");
   };
 
-  let Ok(client) = Client::new(Connection { config: None, package_id }) else {
-    panic!("Couldn't create client");
+  let client = match Client::new(Connection { config: None, package_id, profile: None, gas: GasConfig::default() }) {
+    Ok(client) => client,
+    Err(e) => panic!("Couldn't create client: {e}")
   };
 
   tauri::Builder::default()
@@ -41,8 +43,11 @@ async fn get_distributor(
     return Err("Couldn't create song_obj".to_string());
   };
 
-  let Ok(song) = state.get_song(song_obj).await else {
-    return Err("Couldn't get song".to_string());
+  let song = match state.get_song(song_obj).await {
+    Ok(song) => song,
+    Err(Error::Parse(e)) => return Err(format!("Not a Tuno song: {e}")),
+    Err(e @ Error::Rpc(_)) => return Err(format!("Couldn't reach the network: {e}")),
+    Err(e) => return Err(format!("Couldn't get song: {e}"))
   };
   
   println!("DISTRIBUTORS: {}", song.distributors);
@@ -53,10 +58,11 @@ async fn get_distributor(
     return Err("Couldn't find a distributor".to_string());
   };
 
-  let Ok(
-    tx
-  ) = state.get_payment_transaction(song_obj, addr).await else {
-    return Err("Couldn't create payment transaction".to_string());
+  let tx = match state.get_payment_transaction(song_obj, addr).await {
+    Ok(tx) => tx,
+    Err(Error::Abort { error, .. }) => return Err(format!("Song can't be streamed: {error}")),
+    Err(e @ Error::Rpc(_)) => return Err(format!("Couldn't reach the network: {e}")),
+    Err(e) => return Err(format!("Couldn't create payment transaction: {e}"))
   };
 
   let Ok(raw_transaction) = bcs::to_bytes(&tx) else {
@@ -51,6 +51,10 @@ export interface SongBytes {
      * @generated from protobuf field: bytes data = 1;
      */
     data: Uint8Array;
+    /**
+     * @generated from protobuf field: string mime_type = 2;
+     */
+    mimeType: string;
 }
 // @generated message type with reflection information, may provide speed optimized methods
 class EchoRequest$Type extends MessageType<EchoRequest> {
@@ -105,7 +109,8 @@ export const SongStreamRequest = new SongStreamRequest$Type();
 class SongBytes$Type extends MessageType<SongBytes> {
     constructor() {
         super("tuno.SongBytes", [
-            { no: 1, name: "data", kind: "scalar", T: 12 /*ScalarType.BYTES*/ }
+            { no: 1, name: "data", kind: "scalar", T: 12 /*ScalarType.BYTES*/ },
+            { no: 2, name: "mime_type", kind: "scalar", T: 9 /*ScalarType.STRING*/ }
         ]);
     }
 }